@@ -0,0 +1,78 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::BufReader;
+
+use day1::external_sort::{external_total_distance, ExternalError};
+use day1::metrics::MetricKind;
+use day1::parse::{exit_overflow, parse_lists, report_problems, ParseMode};
+use day1::report::{pair_rows, ReportArgs};
+use day1::table::format_metrics;
+
+#[derive(Parser, Debug)]
+struct Args {
+    file: String,
+    #[arg(long, value_enum, default_value_t = ParseMode::Strict)]
+    mode: ParseMode,
+    /// Comma separated list of metrics to print
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        default_value = "total-distance"
+    )]
+    metrics: Vec<MetricKind>,
+    /// Print every pair of columns as a table, even if there are only two columns
+    #[arg(long)]
+    table: bool,
+    #[command(flatten)]
+    report: ReportArgs,
+    /// Sort the first two columns on disk so the distance is computed with bounded memory
+    #[arg(long)]
+    external: bool,
+    /// Number of values per column held in memory before spilling a sorted run
+    #[arg(
+        long,
+        default_value_t = 1 << 20,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    run_size: usize,
+}
+
+fn run_external(args: &Args) {
+    if args.metrics != [MetricKind::TotalDistance] || args.table {
+        eprintln!("--external only supports the total-distance metric");
+        std::process::exit(2);
+    }
+    if args.report.report.is_some() {
+        eprintln!("--external does not keep the rows needed for --report");
+        std::process::exit(2);
+    }
+    let file = File::open(&args.file).expect("Could not open input file");
+    let (distance, problems) =
+        match external_total_distance(BufReader::new(file), args.mode, args.run_size) {
+            Ok(result) => result,
+            Err(ExternalError::Overflow(e)) => exit_overflow(e),
+            Err(e) => panic!("Could not compute external distance: {}", e),
+        };
+    report_problems(&problems);
+    println!("distance: {}", distance);
+}
+
+fn main() {
+    let args = Args::parse();
+    if args.external {
+        run_external(&args);
+        return;
+    }
+    let (lists, problems) =
+        parse_lists(&args.file, args.mode).expect("Could not parse input lists");
+    report_problems(&problems);
+    args.report
+        .write(|| pair_rows(&lists.pair(0, 1)))
+        .expect("Could not write report");
+    if args.report.stdout_free() {
+        let metrics =
+            format_metrics(&lists, &args.metrics, args.table).unwrap_or_else(|e| exit_overflow(e));
+        print!("{}", metrics);
+    }
+}
@@ -0,0 +1,36 @@
+use clap::Parser;
+
+use day1::metrics::MetricKind;
+use day1::parse::{exit_overflow, parse_lists, report_problems, ParseMode};
+use day1::report::{similarity_rows, ReportArgs};
+use day1::table::format_metrics;
+
+#[derive(Parser, Debug)]
+struct Args {
+    file: String,
+    #[arg(long, value_enum, default_value_t = ParseMode::Strict)]
+    mode: ParseMode,
+    /// Comma separated list of metrics to print
+    #[arg(long, value_enum, value_delimiter = ',', default_value = "similarity")]
+    metrics: Vec<MetricKind>,
+    /// Print every pair of columns as a table, even if there are only two columns
+    #[arg(long)]
+    table: bool,
+    #[command(flatten)]
+    report: ReportArgs,
+}
+
+fn main() {
+    let args = Args::parse();
+    let (lists, problems) =
+        parse_lists(&args.file, args.mode).expect("Could not parse input lists");
+    report_problems(&problems);
+    args.report
+        .write(|| similarity_rows(&lists.pair(0, 1)).unwrap_or_else(|e| exit_overflow(e)))
+        .expect("Could not write report");
+    if args.report.stdout_free() {
+        let metrics =
+            format_metrics(&lists, &args.metrics, args.table).unwrap_or_else(|e| exit_overflow(e));
+        print!("{}", metrics);
+    }
+}
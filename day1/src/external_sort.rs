@@ -0,0 +1,311 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+
+use std::fmt;
+
+use crate::metrics::{OverflowError, TotalDistance};
+use crate::parse::{LineParser, ParseMode, ParseProblem};
+
+/// How many runs are merged at once. Every run is an open file, so merging
+/// them all in one pass would run out of file descriptors on large inputs.
+pub const MAX_MERGE_RUNS: usize = 32;
+
+/// Collects values into sorted runs of at most `run_size` values, spilling
+/// each full run to an anonymous temp file so memory use stays bounded.
+///
+/// Whenever `max_runs` runs of the same level pile up they are merged into
+/// one run of the next level, so only a few files per level are ever open.
+pub struct RunWriter {
+    run_size: usize,
+    max_runs: usize,
+    buffer: Vec<i32>,
+    // levels[i] holds runs made by merging runs of level i - 1
+    levels: Vec<Vec<File>>,
+}
+
+// Writes sorted values to a new temp file, rewound so it can be read back
+fn write_run<I: IntoIterator<Item = io::Result<i32>>>(values: I) -> io::Result<File> {
+    let mut file = tempfile::tempfile()?;
+    {
+        let mut writer = BufWriter::new(&mut file);
+        for value in values {
+            writer.write_all(&value?.to_le_bytes())?;
+        }
+        writer.flush()?;
+    }
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+impl RunWriter {
+    pub fn new(run_size: usize) -> RunWriter {
+        assert!(run_size > 0, "Run size must be positive");
+        RunWriter {
+            run_size,
+            max_runs: MAX_MERGE_RUNS,
+            buffer: Vec::with_capacity(run_size),
+            levels: Vec::new(),
+        }
+    }
+
+    /// Merges at most `max_runs` runs at a time instead of `MAX_MERGE_RUNS`.
+    pub fn with_max_runs(self, max_runs: usize) -> RunWriter {
+        assert!(max_runs > 1, "Must merge at least two runs at a time");
+        RunWriter { max_runs, ..self }
+    }
+
+    pub fn push(&mut self, value: i32) -> io::Result<()> {
+        self.buffer.push(value);
+        if self.buffer.len() >= self.run_size {
+            self.spill()?;
+        }
+        Ok(())
+    }
+
+    fn spill(&mut self) -> io::Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.buffer.sort();
+        let run = write_run(self.buffer.iter().map(|&value| Ok(value)))?;
+        self.buffer.clear();
+        self.add_run(0, run)
+    }
+
+    fn add_run(&mut self, level: usize, run: File) -> io::Result<()> {
+        if self.levels.len() == level {
+            self.levels.push(Vec::new());
+        }
+        self.levels[level].push(run);
+        if self.levels[level].len() >= self.max_runs {
+            let runs = std::mem::take(&mut self.levels[level]);
+            let merged = write_run(MergedRuns::new(runs)?)?;
+            self.add_run(level + 1, merged)?;
+        }
+        Ok(())
+    }
+
+    pub fn num_runs(&self) -> usize {
+        self.levels.iter().map(Vec::len).sum()
+    }
+
+    /// Spills whatever is left and returns all values in sorted order.
+    pub fn finish(mut self) -> io::Result<MergedRuns> {
+        self.spill()?;
+        let mut runs = self.levels.into_iter().flatten().collect::<Vec<File>>();
+        // Each level holds fewer than max_runs, but together they can hold more
+        while runs.len() > self.max_runs {
+            let merged = write_run(MergedRuns::new(runs.drain(..self.max_runs).collect())?)?;
+            runs.push(merged);
+        }
+        MergedRuns::new(runs)
+    }
+}
+
+/// K-way merge of sorted runs, yielding every value in ascending order.
+pub struct MergedRuns {
+    readers: Vec<BufReader<File>>,
+    heap: BinaryHeap<Reverse<(i32, usize)>>,
+}
+
+fn read_value(reader: &mut BufReader<File>) -> io::Result<Option<i32>> {
+    let mut bytes = [0; 4];
+    match reader.read_exact(&mut bytes) {
+        Ok(()) => Ok(Some(i32::from_le_bytes(bytes))),
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+impl MergedRuns {
+    fn new(runs: Vec<File>) -> io::Result<MergedRuns> {
+        let mut readers = runs.into_iter().map(BufReader::new).collect::<Vec<_>>();
+        let mut heap = BinaryHeap::new();
+        for (run, reader) in readers.iter_mut().enumerate() {
+            if let Some(value) = read_value(reader)? {
+                heap.push(Reverse((value, run)));
+            }
+        }
+        Ok(MergedRuns { readers, heap })
+    }
+}
+
+impl Iterator for MergedRuns {
+    type Item = io::Result<i32>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((value, run)) = self.heap.pop()?;
+        match read_value(&mut self.readers[run]) {
+            Ok(Some(next)) => self.heap.push(Reverse((next, run))),
+            Ok(None) => {}
+            Err(e) => return Some(Err(e)),
+        }
+        Some(Ok(value))
+    }
+}
+
+#[derive(Debug)]
+pub enum ExternalError {
+    /// Reading or parsing the input, or spilling runs to disk, failed
+    Input(String),
+    Overflow(OverflowError),
+}
+
+impl fmt::Display for ExternalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExternalError::Input(message) => write!(f, "{}", message),
+            ExternalError::Overflow(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for ExternalError {}
+
+/// Computes the same total distance as `TotalDistance` over the first two
+/// columns, but only ever holds `run_size` values per column in memory.
+pub fn external_total_distance<R: BufRead>(
+    reader: R,
+    mode: ParseMode,
+    run_size: usize,
+) -> Result<(i64, Vec<ParseProblem>), ExternalError> {
+    external_distance_merging(reader, mode, run_size, MAX_MERGE_RUNS)
+}
+
+fn external_distance_merging<R: BufRead>(
+    reader: R,
+    mode: ParseMode,
+    run_size: usize,
+    max_runs: usize,
+) -> Result<(i64, Vec<ParseProblem>), ExternalError> {
+    let mut parser = LineParser::new(mode);
+    let mut left = RunWriter::new(run_size).with_max_runs(max_runs);
+    let mut right = RunWriter::new(run_size).with_max_runs(max_runs);
+    let io_err =
+        |e: io::Error| ExternalError::Input(format!("I/O error during external sort: {}", e));
+
+    for (index, line) in reader.lines().enumerate() {
+        let line = line.map_err(io_err)?;
+        if let Some(entries) = parser
+            .parse(index + 1, &line)
+            .map_err(|e| ExternalError::Input(e.to_string()))?
+        {
+            left.push(entries[0]).map_err(io_err)?;
+            right.push(entries[1]).map_err(io_err)?;
+        }
+    }
+
+    let left = left.finish().map_err(io_err)?;
+    let right = right.finish().map_err(io_err)?;
+    // Stream the merged pairs straight into the fold, stopping at the first read error
+    let mut error = None;
+    let pairs = left.zip(right).map_while(|pair| match pair {
+        (Ok(l), Ok(r)) => Some((l, r)),
+        (Err(e), _) | (_, Err(e)) => {
+            error = Some(e);
+            None
+        }
+    });
+    let distance = TotalDistance::from_sorted_pairs(pairs);
+    if let Some(e) = error {
+        return Err(io_err(e));
+    }
+    Ok((
+        distance.map_err(ExternalError::Overflow)?,
+        parser.into_problems(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locations::LocationLists;
+    use crate::parse::parse_contents;
+
+    // Small deterministic generator so the test does not need a rand dependency
+    fn pseudo_random_lists(n: usize) -> String {
+        let mut state: u64 = 0x2545F4914F6CDD1D;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 200_001) as i32 - 100_000
+        };
+        (0..n)
+            .map(|_| format!("{}   {}\n", next(), next()))
+            .collect()
+    }
+
+    fn in_memory_distance(contents: &str) -> i64 {
+        let (lists, _) = parse_contents(contents, ParseMode::Strict).unwrap();
+        lists.compute(&TotalDistance).unwrap()
+    }
+
+    #[test]
+    fn test_merged_runs_sorted() {
+        let mut writer = RunWriter::new(3);
+        for value in [5, -1, 9, 3, 3, 0, 12, -7] {
+            writer.push(value).unwrap();
+        }
+        assert_eq!(writer.num_runs(), 2);
+        let merged = writer.finish().unwrap().collect::<io::Result<Vec<i32>>>();
+        assert_eq!(merged.unwrap(), vec![-7, -1, 0, 3, 3, 5, 9, 12]);
+    }
+
+    #[test]
+    fn test_external_matches_in_memory() {
+        let contents = pseudo_random_lists(1000);
+        for run_size in [1, 7, 64, 1000, 5000] {
+            let (distance, problems) =
+                external_total_distance(contents.as_bytes(), ParseMode::Strict, run_size).unwrap();
+            assert!(problems.is_empty());
+            assert_eq!(distance, in_memory_distance(&contents));
+        }
+    }
+
+    #[test]
+    fn test_merge_fan_in_is_capped() {
+        let mut writer = RunWriter::new(1).with_max_runs(3);
+        for value in (0..20).rev() {
+            writer.push(value).unwrap();
+            assert!(writer.num_runs() < 3 * 3);
+        }
+        let merged = writer.finish().unwrap().collect::<io::Result<Vec<i32>>>();
+        assert_eq!(merged.unwrap(), (0..20).collect::<Vec<i32>>());
+
+        // Far more runs than the cap, over several levels of merging
+        let contents = pseudo_random_lists(1000);
+        for max_runs in [2, 3, 16] {
+            let (distance, _) =
+                external_distance_merging(contents.as_bytes(), ParseMode::Strict, 7, max_runs)
+                    .unwrap();
+            assert_eq!(distance, in_memory_distance(&contents));
+        }
+    }
+
+    #[test]
+    fn test_external_empty() {
+        let (distance, _) = external_total_distance("".as_bytes(), ParseMode::Strict, 4).unwrap();
+        assert_eq!(
+            distance,
+            LocationLists::from_pair(vec![], vec![])
+                .compute(&TotalDistance)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_external_lenient() {
+        let contents = "3 4\n4 3\nx 2\n2 5\n1 3\n3 9\n3 3\n";
+        let (distance, problems) =
+            external_total_distance(contents.as_bytes(), ParseMode::Lenient, 2).unwrap();
+        assert_eq!(distance, 11);
+        assert_eq!(problems.len(), 1);
+        assert!(matches!(
+            external_total_distance(contents.as_bytes(), ParseMode::Strict, 2),
+            Err(ExternalError::Input(_))
+        ));
+    }
+}
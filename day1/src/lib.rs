@@ -0,0 +1,6 @@
+pub mod external_sort;
+pub mod locations;
+pub mod metrics;
+pub mod parse;
+pub mod report;
+pub mod table;
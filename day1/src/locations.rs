@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use crate::metrics::{Metric, OverflowError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocationLists {
+    columns: Vec<Vec<i32>>,
+    // Input line number of each row, since lenient parsing may skip lines
+    lines: Vec<usize>,
+}
+
+/// Two columns of a `LocationLists`, compared left against right.
+#[derive(Debug, Clone, Copy)]
+pub struct ColumnPair<'a> {
+    pub left: &'a [i32],
+    pub right: &'a [i32],
+    pub lines: &'a [usize],
+}
+
+impl LocationLists {
+    pub fn new(columns: Vec<Vec<i32>>) -> LocationLists {
+        let rows = columns.first().map_or(0, |c| c.len());
+        LocationLists::with_lines(columns, (1..=rows).collect())
+    }
+
+    pub fn with_lines(columns: Vec<Vec<i32>>, lines: Vec<usize>) -> LocationLists {
+        // The parser only ever produces columns of equal length
+        assert!(
+            columns.iter().all(|c| c.len() == lines.len()),
+            "Location lists differ in length"
+        );
+        LocationLists { columns, lines }
+    }
+
+    pub fn from_pair(left: Vec<i32>, right: Vec<i32>) -> LocationLists {
+        LocationLists::new(vec![left, right])
+    }
+
+    pub fn ncols(&self) -> usize {
+        self.columns.len()
+    }
+
+    pub fn column(&self, index: usize) -> &[i32] {
+        &self.columns[index]
+    }
+
+    pub fn left(&self) -> &[i32] {
+        self.column(0)
+    }
+
+    pub fn right(&self) -> &[i32] {
+        self.column(1)
+    }
+
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
+
+    pub fn len(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn pair(&self, left: usize, right: usize) -> ColumnPair<'_> {
+        ColumnPair {
+            left: self.column(left),
+            right: self.column(right),
+            lines: &self.lines,
+        }
+    }
+
+    /// Computes the metric for the first two columns, which is what the puzzle asks for.
+    pub fn compute<M: Metric + ?Sized>(&self, metric: &M) -> Result<i64, OverflowError> {
+        metric.compute(&self.pair(0, 1))
+    }
+
+    /// Computes the metric for every ordered pair of columns, indexed as `[left][right]`.
+    pub fn matrix<M: Metric + ?Sized>(&self, metric: &M) -> Result<Vec<Vec<i64>>, OverflowError> {
+        (0..self.ncols())
+            .map(|left| {
+                (0..self.ncols())
+                    .map(|right| metric.compute(&self.pair(left, right)))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+/// Distance between two locations, widened so it cannot overflow.
+pub fn gap(left: i32, right: i32) -> i64 {
+    (i64::from(left) - i64::from(right)).abs()
+}
+
+/// What a left location adds to the similarity score, or `None` if it does
+/// not fit in an i64.
+pub fn similarity(location: i32, occurrences: usize) -> Option<i64> {
+    i64::try_from(occurrences)
+        .ok()?
+        .checked_mul(i64::from(location))
+}
+
+impl ColumnPair<'_> {
+    /// Row indices of each side in sorted order. The sort is stable, so equal
+    /// locations keep their input order.
+    pub fn sorted_rows(&self) -> (Vec<usize>, Vec<usize>) {
+        let mut left = (0..self.left.len()).collect::<Vec<usize>>();
+        let mut right = left.clone();
+        left.sort_by_key(|&row| self.left[row]);
+        right.sort_by_key(|&row| self.right[row]);
+        (left, right)
+    }
+
+    /// Pairs the smallest left location with the smallest right location, and so on.
+    pub fn sorted_pairs(&self) -> Vec<(i32, i32)> {
+        let mut left = self.left.to_vec();
+        let mut right = self.right.to_vec();
+        left.sort();
+        right.sort();
+        left.into_iter().zip(right).collect()
+    }
+
+    /// Absolute difference of each sorted pair, in pair order.
+    pub fn gaps(&self) -> Vec<i64> {
+        self.sorted_pairs()
+            .iter()
+            .map(|(l, r)| gap(*l, *r))
+            .collect()
+    }
+
+    /// How many times each location appears on the right.
+    pub fn right_counts(&self) -> HashMap<i32, usize> {
+        let mut counts = HashMap::new();
+        for location in self.right {
+            *counts.entry(*location).or_insert(0) += 1;
+        }
+        counts
+    }
+}
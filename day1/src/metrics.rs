@@ -0,0 +1,255 @@
+use clap::ValueEnum;
+use std::fmt;
+
+use crate::locations::{gap, similarity, ColumnPair};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OverflowError {
+    pub metric: &'static str,
+}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} overflowed a 64-bit accumulator", self.metric)
+    }
+}
+
+impl std::error::Error for OverflowError {}
+
+pub trait Metric {
+    fn name(&self) -> &'static str;
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError>;
+}
+
+// Every term is already widened to i64, so only the running total can overflow
+fn checked_sum<I: IntoIterator<Item = Option<i64>>>(
+    metric: &'static str,
+    terms: I,
+) -> Result<i64, OverflowError> {
+    terms
+        .into_iter()
+        .try_fold(0i64, |acc, term| acc.checked_add(term?))
+        .ok_or(OverflowError { metric })
+}
+
+/// Sum of the gaps between the sorted left and right lists (p1)
+pub struct TotalDistance;
+
+/// Each left location weighted by how often it appears in the right list (p2)
+pub struct SimilarityScore;
+
+/// Sum of the squared gaps between the sorted lists
+pub struct SquaredDistance;
+
+/// Largest gap between any sorted pair
+pub struct MaxGap;
+
+/// Median gap between sorted pairs, using the lower median for an even count
+pub struct MedianGap;
+
+impl Metric for TotalDistance {
+    fn name(&self) -> &'static str {
+        "distance"
+    }
+
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError> {
+        TotalDistance::from_sorted_pairs(pair.sorted_pairs())
+    }
+}
+
+impl TotalDistance {
+    /// Folds pairs that the caller has already sorted, e.g. when streaming
+    /// from an external sort, so every path shares the same arithmetic.
+    pub fn from_sorted_pairs<I: IntoIterator<Item = (i32, i32)>>(
+        pairs: I,
+    ) -> Result<i64, OverflowError> {
+        checked_sum(
+            TotalDistance.name(),
+            pairs.into_iter().map(|(l, r)| Some(gap(l, r))),
+        )
+    }
+}
+
+impl Metric for SimilarityScore {
+    fn name(&self) -> &'static str {
+        "similarity"
+    }
+
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError> {
+        let counts = pair.right_counts();
+        checked_sum(
+            self.name(),
+            pair.left
+                .iter()
+                .map(|l| similarity(*l, counts.get(l).copied().unwrap_or(0))),
+        )
+    }
+}
+
+impl Metric for SquaredDistance {
+    fn name(&self) -> &'static str {
+        "squared distance"
+    }
+
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError> {
+        checked_sum(
+            self.name(),
+            pair.gaps().iter().map(|gap| gap.checked_mul(*gap)),
+        )
+    }
+}
+
+impl Metric for MaxGap {
+    fn name(&self) -> &'static str {
+        "max gap"
+    }
+
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError> {
+        Ok(pair.gaps().into_iter().max().unwrap_or(0))
+    }
+}
+
+impl Metric for MedianGap {
+    fn name(&self) -> &'static str {
+        "median gap"
+    }
+
+    fn compute(&self, pair: &ColumnPair) -> Result<i64, OverflowError> {
+        let mut gaps = pair.gaps();
+        if gaps.is_empty() {
+            return Ok(0);
+        }
+        gaps.sort();
+        Ok(gaps[(gaps.len() - 1) / 2])
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum MetricKind {
+    TotalDistance,
+    Similarity,
+    SquaredDistance,
+    MaxGap,
+    MedianGap,
+}
+
+impl MetricKind {
+    pub fn metric(&self) -> Box<dyn Metric> {
+        match self {
+            MetricKind::TotalDistance => Box::new(TotalDistance),
+            MetricKind::Similarity => Box::new(SimilarityScore),
+            MetricKind::SquaredDistance => Box::new(SquaredDistance),
+            MetricKind::MaxGap => Box::new(MaxGap),
+            MetricKind::MedianGap => Box::new(MedianGap),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locations::LocationLists;
+
+    fn example() -> LocationLists {
+        LocationLists::from_pair(vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3])
+    }
+
+    #[test]
+    fn test_total_distance() {
+        assert_eq!(example().compute(&TotalDistance), Ok(11));
+    }
+
+    #[test]
+    fn test_similarity_score() {
+        assert_eq!(example().compute(&SimilarityScore), Ok(31));
+    }
+
+    #[test]
+    #[cfg(target_pointer_width = "64")]
+    fn test_similarity_counts_do_not_wrap() {
+        // Counts past 2^31 used to wrap in an i32
+        assert_eq!(similarity(2, 1 << 31), Some(1 << 32));
+        assert_eq!(similarity(-3, 1 << 40), Some(-3 << 40));
+        assert_eq!(similarity(2, 1 << 62), None);
+        assert_eq!(similarity(1, usize::MAX), None);
+    }
+
+    #[test]
+    fn test_gap_metrics() {
+        // Sorted gaps are 2, 1, 0, 1, 2, 5
+        assert_eq!(example().compute(&SquaredDistance), Ok(35));
+        assert_eq!(example().compute(&MaxGap), Ok(5));
+        assert_eq!(example().compute(&MedianGap), Ok(1));
+    }
+
+    #[test]
+    fn test_empty_lists() {
+        let lists = LocationLists::from_pair(vec![], vec![]);
+        for kind in MetricKind::value_variants() {
+            assert_eq!(lists.compute(kind.metric().as_ref()), Ok(0));
+        }
+    }
+
+    #[test]
+    fn test_matrix() {
+        let lists = LocationLists::new(vec![vec![1, 2], vec![2, 4], vec![2, 2]]);
+        assert_eq!(
+            lists.matrix(&TotalDistance).unwrap(),
+            vec![vec![0, 3, 1], vec![3, 0, 2], vec![1, 2, 0]]
+        );
+        assert_eq!(
+            lists.matrix(&SimilarityScore).unwrap(),
+            vec![vec![3, 2, 4], vec![2, 6, 4], vec![4, 4, 8]]
+        );
+    }
+
+    #[test]
+    fn test_extreme_values_no_overflow() {
+        let n = 1000;
+        let lists = LocationLists::from_pair(vec![i32::MIN; n], vec![i32::MAX; n]);
+        let max_gap = i64::from(i32::MAX) - i64::from(i32::MIN);
+        assert_eq!(lists.compute(&TotalDistance), Ok(max_gap * n as i64));
+        assert_eq!(lists.compute(&MaxGap), Ok(max_gap));
+        assert_eq!(lists.compute(&MedianGap), Ok(max_gap));
+
+        let lists = LocationLists::from_pair(vec![i32::MIN; n], vec![i32::MIN; n]);
+        assert_eq!(
+            lists.compute(&SimilarityScore),
+            Ok(i64::from(i32::MIN) * (n * n) as i64)
+        );
+    }
+
+    #[test]
+    fn test_squared_distance_overflow() {
+        // A single extreme gap squared is already larger than i64::MAX
+        let lists = LocationLists::from_pair(vec![i32::MIN], vec![i32::MAX]);
+        let err = lists.compute(&SquaredDistance).unwrap_err();
+        assert_eq!(err.metric, "squared distance");
+    }
+
+    #[test]
+    fn test_similarity_overflow() {
+        // i32::MAX * 65537 * 65537 is just past i64::MAX
+        let n = 65537;
+        let lists = LocationLists::from_pair(vec![i32::MAX; n], vec![i32::MAX; n]);
+        assert!(lists.compute(&SimilarityScore).is_err());
+        let lists = LocationLists::from_pair(vec![i32::MAX; n - 2], vec![i32::MAX; n - 2]);
+        assert!(lists.compute(&SimilarityScore).is_ok());
+    }
+
+    #[test]
+    fn test_checked_sum_overflow() {
+        // Total distance needs about 2^31 extreme pairs to overflow, so test the
+        // accumulator it shares with the other metrics directly
+        assert_eq!(
+            checked_sum("distance", [Some(i64::MAX - 1), Some(1)]),
+            Ok(i64::MAX)
+        );
+        assert_eq!(
+            checked_sum("distance", [Some(i64::MAX), Some(1)]),
+            Err(OverflowError { metric: "distance" })
+        );
+        // A term that overflowed on its own is reported the same way
+        assert!(checked_sum("distance", [Some(1), None]).is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use clap::ValueEnum;
+use std::fmt;
+use std::fs;
+
+use crate::locations::LocationLists;
+use crate::metrics::OverflowError;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ParseMode {
+    /// Stop at the first malformed line
+    Strict,
+    /// Skip malformed lines and report them all at the end
+    Lenient,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseProblem {
+    pub line: usize,
+    pub column: usize,
+    pub token: String,
+    pub message: String,
+}
+
+impl fmt::Display for ParseProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Line {}, column {}: {} (token '{}')",
+            self.line, self.column, self.message, self.token
+        )
+    }
+}
+
+impl std::error::Error for ParseProblem {}
+
+/// Prints each skipped line and how many there were to stderr.
+pub fn report_problems(problems: &[ParseProblem]) {
+    for problem in problems {
+        eprintln!("skipped: {}", problem);
+    }
+    if !problems.is_empty() {
+        eprintln!("skipped {} malformed lines", problems.len());
+    }
+}
+
+/// Reports a metric that does not fit in 64 bits and exits with an error.
+pub fn exit_overflow(e: OverflowError) -> ! {
+    eprintln!("error: {}", e);
+    std::process::exit(1);
+}
+
+// Splits a line into whitespace separated fields along with their 1-based column
+fn fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut start = None;
+    for (col, (byte, c)) in line.char_indices().enumerate() {
+        match (start, c.is_whitespace()) {
+            (None, false) => start = Some((col, byte)),
+            (Some((start_col, start_byte)), true) => {
+                fields.push((start_col + 1, &line[start_byte..byte]));
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some((start_col, start_byte)) = start {
+        fields.push((start_col + 1, &line[start_byte..]));
+    }
+    fields
+}
+
+// The first well formed line decides how many columns every other line must have
+fn parse_line(
+    line_no: usize,
+    line: &str,
+    expected_columns: Option<usize>,
+) -> Result<Vec<i32>, ParseProblem> {
+    let entries = fields(line);
+    let count_problem = |index: usize, message: String| {
+        let (column, token) = match entries.get(index) {
+            Some((column, token)) => (*column, token.to_string()),
+            None => (line.chars().count() + 1, String::new()),
+        };
+        ParseProblem {
+            line: line_no,
+            column,
+            token,
+            message,
+        }
+    };
+    match expected_columns {
+        Some(expected) if entries.len() != expected => {
+            return Err(count_problem(
+                expected,
+                format!(
+                    "Expected exactly {} entries, found {}",
+                    expected,
+                    entries.len()
+                ),
+            ));
+        }
+        None if entries.len() < 2 => {
+            return Err(count_problem(
+                entries.len(),
+                format!("Expected at least two entries, found {}", entries.len()),
+            ));
+        }
+        _ => {}
+    }
+    entries
+        .iter()
+        .map(|(column, token)| {
+            token.parse::<i32>().map_err(|e| ParseProblem {
+                line: line_no,
+                column: *column,
+                token: token.to_string(),
+                message: format!("Invalid number: {}", e),
+            })
+        })
+        .collect()
+}
+
+/// Parses one line at a time, tracking the column count and any skipped lines.
+pub struct LineParser {
+    mode: ParseMode,
+    expected_columns: Option<usize>,
+    problems: Vec<ParseProblem>,
+}
+
+impl LineParser {
+    pub fn new(mode: ParseMode) -> LineParser {
+        LineParser {
+            mode,
+            expected_columns: None,
+            problems: Vec::new(),
+        }
+    }
+
+    /// Returns the entries of the line, or `None` if lenient mode skipped it.
+    pub fn parse(&mut self, line_no: usize, line: &str) -> Result<Option<Vec<i32>>, ParseProblem> {
+        match parse_line(line_no, line, self.expected_columns) {
+            Ok(entries) => {
+                self.expected_columns = Some(entries.len());
+                Ok(Some(entries))
+            }
+            Err(problem) => match self.mode {
+                ParseMode::Strict => Err(problem),
+                ParseMode::Lenient => {
+                    self.problems.push(problem);
+                    Ok(None)
+                }
+            },
+        }
+    }
+
+    pub fn into_problems(self) -> Vec<ParseProblem> {
+        self.problems
+    }
+}
+
+pub fn parse_contents(
+    contents: &str,
+    mode: ParseMode,
+) -> Result<(LocationLists, Vec<ParseProblem>), ParseProblem> {
+    let mut columns: Vec<Vec<i32>> = Vec::new();
+    let mut lines = Vec::new();
+    let mut parser = LineParser::new(mode);
+
+    for (index, line) in contents.lines().enumerate() {
+        if let Some(entries) = parser.parse(index + 1, line)? {
+            if columns.is_empty() {
+                columns.resize(entries.len(), Vec::new());
+            }
+            for (column, entry) in columns.iter_mut().zip(entries) {
+                column.push(entry);
+            }
+            lines.push(index + 1);
+        }
+    }
+
+    if columns.is_empty() {
+        columns = vec![Vec::new(), Vec::new()];
+    }
+    Ok((
+        LocationLists::with_lines(columns, lines),
+        parser.into_problems(),
+    ))
+}
+
+pub fn parse_lists(
+    file_path: &str,
+    mode: ParseMode,
+) -> Result<(LocationLists, Vec<ParseProblem>), String> {
+    let contents = fs::read_to_string(file_path).map_err(|_| "Could not read file".to_string())?;
+    parse_contents(&contents, mode).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_contents_valid() {
+        let (lists, problems) = parse_contents("3   4\n4 3\n", ParseMode::Strict).unwrap();
+        assert_eq!(lists.left(), &[3, 4]);
+        assert_eq!(lists.right(), &[4, 3]);
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn test_parse_contents_strict_bad_number() {
+        let err = parse_contents("3   4\n4   x3\n", ParseMode::Strict).unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(err.column, 5);
+        assert_eq!(err.token, "x3");
+    }
+
+    #[test]
+    fn test_parse_contents_strict_wrong_field_count() {
+        let err = parse_contents("3 4\n3 4 5\n", ParseMode::Strict).unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 5, "5"));
+        let err = parse_contents("3 4 5\n3 4\n", ParseMode::Strict).unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (2, 4, ""));
+        let err = parse_contents("3\n", ParseMode::Strict).unwrap_err();
+        assert_eq!((err.line, err.column, err.token.as_str()), (1, 2, ""));
+    }
+
+    #[test]
+    fn test_parse_contents_n_columns() {
+        let (lists, problems) = parse_contents("1 2 3\n4 5 6\n", ParseMode::Strict).unwrap();
+        assert_eq!(lists.ncols(), 3);
+        assert_eq!(lists.column(2), &[3, 6]);
+        assert!(problems.is_empty());
+    }
+
+    #[test]
+    fn test_parse_contents_lenient() {
+        let (lists, problems) =
+            parse_contents("3 4\nx 1\n1 2 3\n5 99999999999\n7 8\n", ParseMode::Lenient).unwrap();
+        assert_eq!(lists.left(), &[3, 7]);
+        assert_eq!(lists.right(), &[4, 8]);
+        assert_eq!(lists.lines(), &[1, 5]);
+        let lines = problems.iter().map(|p| p.line).collect::<Vec<usize>>();
+        assert_eq!(lines, vec![2, 3, 4]);
+        assert_eq!(problems[2].token, "99999999999");
+    }
+}
@@ -0,0 +1,253 @@
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::locations::{gap, similarity, ColumnPair};
+use crate::metrics::{Metric, OverflowError, SimilarityScore};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ReportFormat {
+    Csv,
+    Json,
+}
+
+/// One sorted pair from the distance computation (p1).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PairRow {
+    pub rank: usize,
+    pub left: i32,
+    pub left_line: usize,
+    pub right: i32,
+    pub right_line: usize,
+    pub distance: i64,
+}
+
+/// One left location and what it adds to the similarity score (p2).
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SimilarityRow {
+    pub line: usize,
+    pub value: i32,
+    pub occurrences: usize,
+    pub contribution: i64,
+}
+
+pub trait ReportRow: Serialize {
+    fn header() -> &'static [&'static str];
+    fn fields(&self) -> Vec<String>;
+}
+
+impl ReportRow for PairRow {
+    fn header() -> &'static [&'static str] {
+        &[
+            "rank",
+            "left",
+            "left_line",
+            "right",
+            "right_line",
+            "distance",
+        ]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.rank.to_string(),
+            self.left.to_string(),
+            self.left_line.to_string(),
+            self.right.to_string(),
+            self.right_line.to_string(),
+            self.distance.to_string(),
+        ]
+    }
+}
+
+impl ReportRow for SimilarityRow {
+    fn header() -> &'static [&'static str] {
+        &["line", "value", "occurrences", "contribution"]
+    }
+
+    fn fields(&self) -> Vec<String> {
+        vec![
+            self.line.to_string(),
+            self.value.to_string(),
+            self.occurrences.to_string(),
+            self.contribution.to_string(),
+        ]
+    }
+}
+
+pub fn pair_rows(pair: &ColumnPair) -> Vec<PairRow> {
+    let (left_rows, right_rows) = pair.sorted_rows();
+    left_rows
+        .into_iter()
+        .zip(right_rows)
+        .enumerate()
+        .map(|(rank, (l, r))| PairRow {
+            rank: rank + 1,
+            left: pair.left[l],
+            left_line: pair.lines[l],
+            right: pair.right[r],
+            right_line: pair.lines[r],
+            distance: gap(pair.left[l], pair.right[r]),
+        })
+        .collect()
+}
+
+/// Fails like `SimilarityScore` if a single contribution does not fit in an i64.
+pub fn similarity_rows(pair: &ColumnPair) -> Result<Vec<SimilarityRow>, OverflowError> {
+    let counts = pair.right_counts();
+    pair.left
+        .iter()
+        .zip(pair.lines)
+        .map(|(value, line)| {
+            let occurrences = counts.get(value).copied().unwrap_or(0);
+            let contribution = similarity(*value, occurrences).ok_or(OverflowError {
+                metric: SimilarityScore.name(),
+            })?;
+            Ok(SimilarityRow {
+                line: *line,
+                value: *value,
+                occurrences,
+                contribution,
+            })
+        })
+        .collect()
+}
+
+pub fn write_report<W: Write, T: ReportRow>(
+    mut out: W,
+    rows: &[T],
+    format: ReportFormat,
+) -> io::Result<()> {
+    match format {
+        ReportFormat::Csv => {
+            writeln!(out, "{}", T::header().join(","))?;
+            for row in rows {
+                writeln!(out, "{}", row.fields().join(","))?;
+            }
+        }
+        ReportFormat::Json => {
+            serde_json::to_writer_pretty(&mut out, rows)?;
+            writeln!(out)?;
+        }
+    }
+    Ok(())
+}
+
+/// The named file, or stdout when there is none.
+pub fn open_output(path: Option<&str>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+/// Options for exporting the per-row breakdown behind a metric.
+#[derive(Args, Debug)]
+pub struct ReportArgs {
+    /// Export the per-row breakdown of the first two columns
+    #[arg(long, value_enum)]
+    pub report: Option<ReportFormat>,
+    /// Write the report to this file instead of stdout
+    #[arg(long, requires = "report")]
+    pub report_file: Option<String>,
+}
+
+impl ReportArgs {
+    /// Writes the report if one was asked for, only building the rows then.
+    pub fn write<T: ReportRow>(&self, rows: impl FnOnce() -> Vec<T>) -> io::Result<()> {
+        let Some(format) = self.report else {
+            return Ok(());
+        };
+        let mut out = open_output(self.report_file.as_deref())?;
+        write_report(&mut out, &rows(), format)?;
+        out.flush()
+    }
+
+    /// Whether stdout is free for the metrics, which it isn't when the report
+    /// goes there and has to stay machine readable.
+    pub fn stdout_free(&self) -> bool {
+        self.report.is_none() || self.report_file.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::locations::LocationLists;
+    use crate::metrics::{SimilarityScore, TotalDistance};
+
+    fn example() -> LocationLists {
+        LocationLists::from_pair(vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3])
+    }
+
+    #[test]
+    fn test_pair_rows() {
+        let lists = example();
+        let rows = pair_rows(&lists.pair(0, 1));
+        assert_eq!(
+            rows[0],
+            PairRow {
+                rank: 1,
+                left: 1,
+                left_line: 4,
+                right: 3,
+                right_line: 2,
+                distance: 2,
+            }
+        );
+        // Ties keep input order
+        assert_eq!(rows[2].left_line, 1);
+        assert_eq!(rows[3].left_line, 5);
+        let total = rows.iter().map(|r| r.distance).sum::<i64>();
+        assert_eq!(Ok(total), lists.compute(&TotalDistance));
+    }
+
+    #[test]
+    fn test_similarity_rows() {
+        let lists = example();
+        let rows = similarity_rows(&lists.pair(0, 1)).unwrap();
+        assert_eq!(rows.len(), 6);
+        assert_eq!(
+            rows[0],
+            SimilarityRow {
+                line: 1,
+                value: 3,
+                occurrences: 3,
+                contribution: 9,
+            }
+        );
+        let total = rows.iter().map(|r| r.contribution).sum::<i64>();
+        assert_eq!(Ok(total), lists.compute(&SimilarityScore));
+    }
+
+    #[test]
+    fn test_write_report_csv() {
+        let lists = LocationLists::from_pair(vec![2, 1], vec![1, 5]);
+        let mut out = Vec::new();
+        write_report(&mut out, &pair_rows(&lists.pair(0, 1)), ReportFormat::Csv).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "rank,left,left_line,right,right_line,distance\n\
+             1,1,2,1,1,0\n\
+             2,2,1,5,2,3\n"
+        );
+    }
+
+    #[test]
+    fn test_write_report_json() {
+        let lists = LocationLists::from_pair(vec![2], vec![2]);
+        let mut out = Vec::new();
+        write_report(
+            &mut out,
+            &similarity_rows(&lists.pair(0, 1)).unwrap(),
+            ReportFormat::Json,
+        )
+        .unwrap();
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            parsed,
+            serde_json::json!([{"line": 1, "value": 2, "occurrences": 1, "contribution": 2}])
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::fmt::Write;
+
+use crate::locations::LocationLists;
+use crate::metrics::{MetricKind, OverflowError};
+
+/// One `name: value` line per metric, or a matrix per metric when there are
+/// more than two columns or `table` is set.
+pub fn format_metrics(
+    lists: &LocationLists,
+    kinds: &[MetricKind],
+    table: bool,
+) -> Result<String, OverflowError> {
+    let mut out = String::new();
+    for kind in kinds {
+        let metric = kind.metric();
+        if table || lists.ncols() > 2 {
+            out.push_str(&format_matrix(
+                metric.name(),
+                &lists.matrix(metric.as_ref())?,
+            ));
+        } else {
+            let value = lists.compute(metric.as_ref())?;
+            writeln!(out, "{}: {}", metric.name(), value).unwrap();
+        }
+    }
+    Ok(out)
+}
+
+/// Renders a square metric matrix with the metric name in the corner and
+/// 1-based column labels along both axes.
+pub fn format_matrix(title: &str, matrix: &[Vec<i64>]) -> String {
+    let labels = (1..=matrix.len())
+        .map(|i| format!("col{}", i))
+        .collect::<Vec<String>>();
+    let label_width = labels
+        .iter()
+        .map(|l| l.len())
+        .chain(std::iter::once(title.len()))
+        .max()
+        .unwrap_or(0);
+    let cell_width = matrix
+        .iter()
+        .flatten()
+        .map(|v| v.to_string().len())
+        .chain(labels.iter().map(|l| l.len()))
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    write!(out, "{:<w$}", title, w = label_width).unwrap();
+    for label in &labels {
+        write!(out, "  {:>w$}", label, w = cell_width).unwrap();
+    }
+    out.push('\n');
+    for (label, row) in labels.iter().zip(matrix) {
+        write!(out, "{:<w$}", label, w = label_width).unwrap();
+        for value in row {
+            write!(out, "  {:>w$}", value, w = cell_width).unwrap();
+        }
+        out.push('\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_metrics() {
+        let lists = LocationLists::from_pair(vec![3, 4, 2, 1, 3, 3], vec![4, 3, 5, 3, 9, 3]);
+        let kinds = [MetricKind::TotalDistance, MetricKind::Similarity];
+        assert_eq!(
+            format_metrics(&lists, &kinds, false).unwrap(),
+            "distance: 11\nsimilarity: 31\n"
+        );
+        assert!(format_metrics(&lists, &kinds[..1], true)
+            .unwrap()
+            .starts_with("distance"));
+    }
+
+    #[test]
+    fn test_format_matrix() {
+        let table = format_matrix("distance", &[vec![0, 12], vec![12, 0]]);
+        assert_eq!(
+            table,
+            "distance  col1  col2\n\
+             col1         0    12\n\
+             col2        12     0\n"
+        );
+    }
+}
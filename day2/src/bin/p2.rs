@@ -0,0 +1,34 @@
+use clap::Parser;
+
+use day2::dampener::check_report;
+use day2::diagnostics::{format_explanation, ReportCheck};
+use day2::parse_reports;
+use day2::policy::PolicyArgs;
+
+#[derive(Parser, Debug)]
+struct Args {
+    file: String,
+    /// Maximum number of levels the Problem Dampener may remove from a report
+    #[arg(short, long, default_value_t = 1)]
+    k: usize,
+    #[command(flatten)]
+    policy: PolicyArgs,
+    /// Print why each report is safe or unsafe, plus a histogram of failure reasons
+    #[arg(long)]
+    explain: bool,
+}
+
+fn main() {
+    let args = Args::parse();
+    let policy = args.policy.resolve().unwrap();
+    let reports = parse_reports(&args.file).unwrap();
+    let checks = reports
+        .iter()
+        .map(|report| check_report(report, args.k, &policy))
+        .collect::<Vec<ReportCheck>>();
+    if args.explain {
+        print!("{}", format_explanation(&reports, &checks));
+    }
+    let safe = checks.iter().filter(|c| c.is_safe()).count();
+    println!("safe: {}", safe);
+}
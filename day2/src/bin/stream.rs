@@ -0,0 +1,50 @@
+use clap::Parser;
+use std::io;
+use std::process;
+
+use day2::policy::PolicyArgs;
+use day2::window::{readings, Transition, WindowClassifier};
+
+/// Reads levels from stdin and prints every time a rolling window flips
+/// between safe and unsafe, both as given and with the Problem Dampener.
+#[derive(Parser, Debug)]
+struct Args {
+    /// Number of readings in each window
+    #[arg(short, long, default_value_t = 5)]
+    window: usize,
+    /// Maximum number of levels the Problem Dampener may remove from a window
+    #[arg(short, long, default_value_t = 1)]
+    k: usize,
+    #[command(flatten)]
+    policy: PolicyArgs,
+}
+
+fn print_transition(label: &str, transition: &Transition) {
+    println!(
+        "{} {} at readings {}..={}",
+        label,
+        if transition.safe { "safe" } else { "unsafe" },
+        transition.start,
+        transition.end
+    );
+}
+
+fn main() {
+    let args = Args::parse();
+    let policy = args.policy.resolve().unwrap();
+    let mut raw = WindowClassifier::new(args.window, 0, policy.clone()).unwrap();
+    let mut dampened = WindowClassifier::new(args.window, args.k, policy).unwrap();
+
+    for reading in readings(io::stdin().lock()) {
+        let level = reading.unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(1);
+        });
+        if let Some(transition) = raw.push(level) {
+            print_transition("raw", &transition);
+        }
+        if let Some(transition) = dampened.push(level) {
+            print_transition("dampened", &transition);
+        }
+    }
+}
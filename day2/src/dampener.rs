@@ -0,0 +1,292 @@
+use crate::diagnostics::{first_violation, ReportCheck};
+use crate::policy::SafetyPolicy;
+
+// Fewest removals that leave a safe report moving in one direction, or None if
+// more than max_removals are needed.
+//
+// best[i] is the fewest removals among report[..=i] such that level i is kept and
+// the kept levels are safe. Keeping i right after p removes the i - p - 1 levels
+// between them, so only the max_removals + 1 closest predecessors can matter,
+// which makes this O(n * k).
+fn find_removals_in_direction(
+    report: &[i32],
+    max_removals: usize,
+    policy: &SafetyPolicy,
+    increasing: bool,
+) -> Option<Vec<usize>> {
+    let n = report.len();
+    if n == 0 {
+        return Some(vec![]);
+    }
+
+    let mut best: Vec<Option<usize>> = vec![None; n];
+    let mut prev_kept: Vec<Option<usize>> = vec![None; n];
+    for i in 0..n {
+        // Dropping everything before i
+        if i <= max_removals {
+            best[i] = Some(i);
+        }
+        for p in i.saturating_sub(max_removals + 1)..i {
+            let Some(removed) = best[p] else { continue };
+            let removed = removed + (i - p - 1);
+            if removed > max_removals || !policy.is_safe_step(report[p], report[i], increasing) {
+                continue;
+            }
+            if best[i].is_none_or(|b| removed < b) {
+                best[i] = Some(removed);
+                prev_kept[i] = Some(p);
+            }
+        }
+    }
+
+    // Everything after the last kept level is dropped too
+    let (last, _) = (0..n)
+        .filter_map(|i| best[i].map(|b| (i, b + (n - 1 - i))))
+        .filter(|(_, removed)| *removed <= max_removals)
+        .min_by_key(|(_, removed)| *removed)?;
+
+    let mut kept = vec![false; n];
+    let mut cur = Some(last);
+    while let Some(i) = cur {
+        kept[i] = true;
+        cur = prev_kept[i];
+    }
+    Some((0..n).filter(|i| !kept[*i]).collect())
+}
+
+/// Returns the indices of the fewest levels whose removal makes the report safe
+/// under the policy, or None if that takes more than `max_removals` levels.
+pub fn find_removals(
+    report: &[i32],
+    max_removals: usize,
+    policy: &SafetyPolicy,
+) -> Option<Vec<usize>> {
+    policy
+        .directions()
+        .iter()
+        .filter_map(|increasing| {
+            find_removals_in_direction(report, max_removals, policy, *increasing)
+        })
+        .min_by_key(|removed| removed.len())
+}
+
+/// Checks the report as given, then lets the dampener remove up to
+/// `max_removals` levels if it was unsafe.
+pub fn check_report(report: &[i32], max_removals: usize, policy: &SafetyPolicy) -> ReportCheck {
+    let Some(violation) = first_violation(report, policy) else {
+        return ReportCheck::Safe;
+    };
+    match find_removals(report, max_removals, policy) {
+        Some(removed) => ReportCheck::Dampened { violation, removed },
+        None => ReportCheck::Unsafe(violation),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::policy::Direction;
+    use std::cmp::Ordering::{Greater, Less};
+
+    const MAX_DIFF: i64 = 3;
+
+    fn check_default(report: &[i32], max_removals: usize) -> i32 {
+        check_report(report, max_removals, &SafetyPolicy::default()).is_safe() as i32
+    }
+
+    fn check_default_rich(report: &[i32], max_removals: usize) -> ReportCheck {
+        check_report(report, max_removals, &SafetyPolicy::default())
+    }
+
+    fn find_default(report: &[i32], max_removals: usize) -> Option<Vec<usize>> {
+        find_removals(report, max_removals, &SafetyPolicy::default())
+    }
+
+    // The original brute force dampener, kept as a reference for the DP
+    fn check_report_no_skip(report: &[i32]) -> i32 {
+        if report.len() <= 1 {
+            return 1;
+        }
+
+        let l0 = report[0];
+        let l1 = report[1];
+        let mut ord: std::cmp::Ordering = Greater;
+
+        if l0 < l1 {
+            ord = Less;
+        }
+
+        let mut prev_level = l0;
+        for level in &report[1..] {
+            if prev_level.cmp(level) != ord {
+                return 0;
+            }
+            if i64::from(prev_level - level).abs() > MAX_DIFF {
+                return 0;
+            }
+            prev_level = *level;
+        }
+        1
+    }
+
+    fn check_report_brute_force(report: &[i32]) -> i32 {
+        let pass = check_report_no_skip(report);
+        if pass > 0 {
+            return pass;
+        }
+        for pos in 0..report.len() {
+            let mut skipped = Vec::new();
+            skipped.extend_from_slice(&report[..pos]);
+            skipped.extend_from_slice(&report[pos + 1..]);
+            let pass = check_report_no_skip(&skipped);
+            if pass > 0 {
+                return pass;
+            }
+        }
+        0
+    }
+
+    #[test]
+    fn test_check_report_nonleading() {
+        assert_eq!(check_default(&[1, 2, 3, 4], 1), 1); //Good
+        assert_eq!(check_default(&[1, 3, 3, 4], 1), 1); // Eq element
+        assert_eq!(check_default(&[1, 3, 3, 4, 4], 1), 0); // 2 Eq element
+        assert_eq!(check_default(&[1, 5, 7, 9, 33], 1), 0); // 2 Gap too large
+    }
+
+    #[test]
+    fn test_check_report_leading() {
+        assert_eq!(check_default(&[2, 1, 2, 3, 4], 1), 1); // Skip first, ooo
+        assert_eq!(check_default(&[7, 1, 2, 3, 4], 1), 1); // Skip first, ooo and gap too large
+        assert_eq!(check_default(&[1, 10, 11, 12, 13], 1), 1); // Skip second, gap too large
+        assert_eq!(check_default(&[16, 15, 19, 22, 23, 25], 1), 1); // 15->19 gap too large but 15 should be dropped, not 19
+    }
+
+    #[test]
+    fn test_examples() {
+        assert_eq!(check_default(&[7, 6, 4, 2, 1], 1), 1);
+        assert_eq!(check_default(&[1, 2, 7, 8, 9], 1), 0);
+        assert_eq!(check_default(&[9, 7, 6, 2, 1], 1), 0);
+        assert_eq!(check_default(&[1, 3, 2, 4, 5], 1), 1);
+        assert_eq!(check_default(&[8, 6, 4, 4, 1], 1), 1);
+        assert_eq!(check_default(&[1, 3, 6, 7, 9], 1), 1);
+    }
+
+    #[test]
+    fn test_find_removals() {
+        assert_eq!(find_default(&[7, 6, 4, 2, 1], 0), Some(vec![]));
+        assert_eq!(find_default(&[1, 3, 2, 4, 5], 1), Some(vec![2]));
+        assert_eq!(find_default(&[8, 6, 4, 4, 1], 1), Some(vec![3]));
+        assert_eq!(find_default(&[1, 2, 7, 8, 9], 1), None);
+        assert_eq!(find_default(&[1, 2, 7, 8, 9], 2), Some(vec![0, 1]));
+        assert_eq!(find_default(&[1, 9], 2), Some(vec![1]));
+        assert_eq!(find_default(&[1, 9, 20], 2), Some(vec![1, 2]));
+        assert_eq!(find_default(&[], 0), Some(vec![]));
+    }
+
+    #[test]
+    fn test_removals_leave_safe_report() {
+        let report = [5, 1, 6, 7, 7, 20, 8, 12, 10];
+        let removed = find_default(&report, 4).unwrap();
+        let kept = report
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| !removed.contains(i))
+            .map(|(_, l)| *l)
+            .collect::<Vec<i32>>();
+        assert_eq!(removed.len(), 4);
+        assert_eq!(check_report_no_skip(&kept), 1);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        // Small deterministic generator covering lots of short noisy reports
+        let mut state: u32 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) % 8
+        };
+        for _ in 0..20000 {
+            let len = next() as usize;
+            let report = (0..len).map(|_| next() as i32).collect::<Vec<i32>>();
+            assert_eq!(
+                check_default(&report, 0),
+                check_report_no_skip(&report),
+                "k=0 {:?}",
+                report
+            );
+            assert_eq!(
+                check_default(&report, 1),
+                check_report_brute_force(&report),
+                "k=1 {:?}",
+                report
+            );
+        }
+    }
+
+    #[test]
+    fn test_custom_policy() {
+        let policy = SafetyPolicy {
+            min_step: 2,
+            max_step: 4,
+            allow_plateaus: true,
+            direction: Direction::Decreasing,
+        };
+        assert_eq!(
+            check_report(&[9, 7, 7, 3, 1], 0, &policy),
+            ReportCheck::Safe
+        );
+        assert!(!check_report(&[9, 8, 6], 0, &policy).is_safe());
+        assert_eq!(find_removals(&[9, 8, 6], 1, &policy), Some(vec![1]));
+        assert!(!check_report(&[1, 3, 5], 1, &policy).is_safe());
+    }
+
+    #[test]
+    fn test_check_report_names_removed_level() {
+        let check = check_default_rich(&[1, 3, 2, 4, 5], 1);
+        match check {
+            ReportCheck::Dampened { violation, removed } => {
+                assert_eq!((violation.first, violation.second), (1, 2));
+                assert_eq!(removed, vec![2]);
+            }
+            _ => panic!("Expected a dampened report, got {:?}", check),
+        }
+    }
+
+    #[test]
+    fn test_first_violation_agrees_with_dp() {
+        let policies = [
+            SafetyPolicy::default(),
+            SafetyPolicy {
+                min_step: 2,
+                max_step: 4,
+                allow_plateaus: true,
+                direction: Direction::Any,
+            },
+            SafetyPolicy {
+                min_step: 1,
+                max_step: 2,
+                allow_plateaus: false,
+                direction: Direction::Decreasing,
+            },
+        ];
+        let mut state: u32 = 777;
+        let mut next = || {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) % 7
+        };
+        for _ in 0..20000 {
+            let len = next() as usize;
+            let report = (0..len).map(|_| next() as i32).collect::<Vec<i32>>();
+            for policy in &policies {
+                assert_eq!(
+                    first_violation(&report, policy).is_none(),
+                    find_removals(&report, 0, policy) == Some(vec![]),
+                    "{:?} {:?}",
+                    report,
+                    policy
+                );
+            }
+        }
+    }
+}
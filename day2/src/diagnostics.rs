@@ -0,0 +1,262 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::policy::{Direction, SafetyPolicy};
+
+/// The safety rule a pair of adjacent levels broke.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Rule {
+    /// The levels switched between increasing and decreasing
+    DirectionChange,
+    /// The levels moved against the direction the policy requires
+    WrongDirection,
+    StepTooLarge,
+    StepTooSmall,
+    /// Two adjacent levels were equal and the policy does not allow plateaus
+    EqualLevels,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Rule::DirectionChange => "direction change",
+                Rule::WrongDirection => "wrong direction",
+                Rule::StepTooLarge => "step too large",
+                Rule::StepTooSmall => "step too small",
+                Rule::EqualLevels => "equal levels",
+            }
+        )
+    }
+}
+
+/// The first pair of adjacent levels, by index, that broke a rule.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Violation {
+    pub first: usize,
+    pub second: usize,
+    pub rule: Rule,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReportCheck {
+    Safe,
+    /// Unsafe as given, but safe once the levels at `removed` are dropped
+    Dampened {
+        violation: Violation,
+        removed: Vec<usize>,
+    },
+    Unsafe(Violation),
+}
+
+impl ReportCheck {
+    pub fn is_safe(&self) -> bool {
+        !matches!(self, ReportCheck::Unsafe(_))
+    }
+
+    pub fn violation(&self) -> Option<&Violation> {
+        match self {
+            ReportCheck::Safe => None,
+            ReportCheck::Dampened { violation, .. } => Some(violation),
+            ReportCheck::Unsafe(violation) => Some(violation),
+        }
+    }
+
+    pub fn describe(&self, report: &[i32]) -> String {
+        let pair = |v: &Violation| {
+            format!(
+                "{} at levels {} and {} ({} -> {})",
+                v.rule, v.first, v.second, report[v.first], report[v.second]
+            )
+        };
+        match self {
+            ReportCheck::Safe => "safe".to_string(),
+            ReportCheck::Dampened { violation, removed } => {
+                let removed = removed
+                    .iter()
+                    .map(|i| format!("level {} ({})", i, report[*i]))
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                format!("safe after removing {}; {}", removed, pair(violation))
+            }
+            ReportCheck::Unsafe(violation) => format!("unsafe: {}", pair(violation)),
+        }
+    }
+}
+
+/// Walks the report without removing anything and returns the first broken rule.
+///
+/// With `Direction::Any` the first step that is not a plateau decides the
+/// direction, matching how the puzzle reads the first two levels.
+pub fn first_violation(report: &[i32], policy: &SafetyPolicy) -> Option<Violation> {
+    let mut increasing = match policy.direction {
+        Direction::Any => None,
+        Direction::Increasing => Some(true),
+        Direction::Decreasing => Some(false),
+    };
+    for second in 1..report.len() {
+        let first = second - 1;
+        let violation = |rule| {
+            Some(Violation {
+                first,
+                second,
+                rule,
+            })
+        };
+        let step = i64::from(report[second]) - i64::from(report[first]);
+        if step == 0 {
+            if !policy.allow_plateaus {
+                return violation(Rule::EqualLevels);
+            }
+            continue;
+        }
+        match increasing {
+            None => increasing = Some(step > 0),
+            Some(inc) if inc != (step > 0) => {
+                return match policy.direction {
+                    Direction::Any => violation(Rule::DirectionChange),
+                    _ => violation(Rule::WrongDirection),
+                };
+            }
+            _ => {}
+        }
+        if step.abs() > policy.max_step {
+            return violation(Rule::StepTooLarge);
+        }
+        if step.abs() < policy.min_step {
+            return violation(Rule::StepTooSmall);
+        }
+    }
+    None
+}
+
+/// Counts how often each rule was the reason a report was unsafe as given,
+/// including reports the dampener then fixed.
+pub fn failure_histogram(checks: &[ReportCheck]) -> BTreeMap<Rule, usize> {
+    let mut histogram = BTreeMap::new();
+    for check in checks {
+        if let Some(violation) = check.violation() {
+            *histogram.entry(violation.rule).or_insert(0) += 1;
+        }
+    }
+    histogram
+}
+
+/// One line per report followed by the histogram of failure reasons.
+pub fn format_explanation(reports: &[Vec<i32>], checks: &[ReportCheck]) -> String {
+    let mut out = String::new();
+    for (index, (report, check)) in reports.iter().zip(checks).enumerate() {
+        out.push_str(&format!(
+            "report {}: {}\n",
+            index + 1,
+            check.describe(report)
+        ));
+    }
+    let dampened = checks
+        .iter()
+        .filter(|c| matches!(c, ReportCheck::Dampened { .. }))
+        .count();
+    let unsafe_count = checks.iter().filter(|c| !c.is_safe()).count();
+    out.push_str(&format!(
+        "failure reasons ({} unsafe, {} dampened):\n",
+        unsafe_count, dampened
+    ));
+    for (rule, count) in failure_histogram(checks) {
+        out.push_str(&format!("  {}: {}\n", rule, count));
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violation(first: usize, rule: Rule) -> Option<Violation> {
+        Some(Violation {
+            first,
+            second: first + 1,
+            rule,
+        })
+    }
+
+    #[test]
+    fn test_first_violation_examples() {
+        let policy = SafetyPolicy::default();
+        assert_eq!(first_violation(&[7, 6, 4, 2, 1], &policy), None);
+        assert_eq!(
+            first_violation(&[1, 2, 7, 8, 9], &policy),
+            violation(1, Rule::StepTooLarge)
+        );
+        assert_eq!(
+            first_violation(&[1, 3, 2, 4, 5], &policy),
+            violation(1, Rule::DirectionChange)
+        );
+        assert_eq!(
+            first_violation(&[8, 6, 4, 4, 1], &policy),
+            violation(2, Rule::EqualLevels)
+        );
+    }
+
+    #[test]
+    fn test_first_violation_policy_rules() {
+        let policy = SafetyPolicy {
+            min_step: 2,
+            max_step: 3,
+            allow_plateaus: true,
+            direction: Direction::Increasing,
+        };
+        assert_eq!(first_violation(&[1, 1, 3, 5], &policy), None);
+        assert_eq!(
+            first_violation(&[1, 3, 4], &policy),
+            violation(1, Rule::StepTooSmall)
+        );
+        assert_eq!(
+            first_violation(&[5, 3], &policy),
+            violation(0, Rule::WrongDirection)
+        );
+    }
+
+    #[test]
+    fn test_failure_histogram() {
+        let v = Violation {
+            first: 0,
+            second: 1,
+            rule: Rule::EqualLevels,
+        };
+        let checks = vec![
+            ReportCheck::Safe,
+            ReportCheck::Unsafe(v),
+            ReportCheck::Dampened {
+                violation: v,
+                removed: vec![0],
+            },
+            ReportCheck::Unsafe(Violation {
+                rule: Rule::StepTooLarge,
+                ..v
+            }),
+        ];
+        let histogram = failure_histogram(&checks);
+        assert_eq!(histogram.get(&Rule::EqualLevels), Some(&2));
+        assert_eq!(histogram.get(&Rule::StepTooLarge), Some(&1));
+        assert_eq!(histogram.get(&Rule::DirectionChange), None);
+    }
+
+    #[test]
+    fn test_describe() {
+        let report = [1, 3, 2, 4, 5];
+        let check = ReportCheck::Dampened {
+            violation: Violation {
+                first: 1,
+                second: 2,
+                rule: Rule::DirectionChange,
+            },
+            removed: vec![2],
+        };
+        assert_eq!(
+            check.describe(&report),
+            "safe after removing level 2 (2); direction change at levels 1 and 2 (3 -> 2)"
+        );
+    }
+}
@@ -0,0 +1,22 @@
+pub mod dampener;
+pub mod diagnostics;
+pub mod policy;
+pub mod window;
+
+use std::fs;
+
+pub fn parse_reports(file_path: &str) -> Result<Vec<Vec<i32>>, String> {
+    let contents = fs::read_to_string(file_path).map_err(|_| "Could not read file".to_string())?;
+    let mut reports = Vec::new();
+
+    for line in contents.lines() {
+        let levels_raw: Vec<&str> = line.split_whitespace().collect();
+        let levels: Vec<i32> = levels_raw
+            .iter()
+            .map(|s| s.parse().map_err(|_| format!("Failed to parse '{}'", s)))
+            .collect::<Result<Vec<i32>, String>>()?;
+        reports.push(levels);
+    }
+
+    Ok(reports)
+}
@@ -0,0 +1,225 @@
+use clap::{Args, ValueEnum};
+use std::fs;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Direction {
+    /// Either all increasing or all decreasing
+    Any,
+    Increasing,
+    Decreasing,
+}
+
+/// The rules a report has to follow to be considered safe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SafetyPolicy {
+    pub min_step: i64,
+    pub max_step: i64,
+    pub allow_plateaus: bool,
+    pub direction: Direction,
+}
+
+impl Default for SafetyPolicy {
+    // The rules from the puzzle
+    fn default() -> Self {
+        SafetyPolicy {
+            min_step: 1,
+            max_step: 3,
+            allow_plateaus: false,
+            direction: Direction::Any,
+        }
+    }
+}
+
+impl SafetyPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.min_step < 1 {
+            return Err(format!(
+                "min_step must be at least 1, got {} (use allow_plateaus for equal levels)",
+                self.min_step
+            ));
+        }
+        if self.max_step < self.min_step {
+            return Err(format!(
+                "max_step ({}) is smaller than min_step ({})",
+                self.max_step, self.min_step
+            ));
+        }
+        Ok(())
+    }
+
+    /// Whether the report may be checked as increasing and/or decreasing.
+    pub fn directions(&self) -> &'static [bool] {
+        match self.direction {
+            Direction::Any => &[true, false],
+            Direction::Increasing => &[true],
+            Direction::Decreasing => &[false],
+        }
+    }
+
+    pub fn is_safe_step(&self, prev: i32, next: i32, increasing: bool) -> bool {
+        let step = if increasing {
+            i64::from(next) - i64::from(prev)
+        } else {
+            i64::from(prev) - i64::from(next)
+        };
+        if step == 0 {
+            return self.allow_plateaus;
+        }
+        (self.min_step..=self.max_step).contains(&step)
+    }
+
+    /// Parses `key = value` lines, ignoring blank lines and `#` comments.
+    /// Keys that are not present keep their default.
+    pub fn from_config(contents: &str) -> Result<SafetyPolicy, String> {
+        let policy = SafetyPolicy::parse_config(contents)?;
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    // Validation is left to the caller so command line flags can still fix up a file
+    fn parse_config(contents: &str) -> Result<SafetyPolicy, String> {
+        let mut policy = SafetyPolicy::default();
+        for (index, raw_line) in contents.lines().enumerate() {
+            let line_no = index + 1;
+            let line = raw_line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| format!("Line {}: Expected 'key = value'", line_no))?;
+            let (key, value) = (key.trim(), value.trim());
+            let bad_value =
+                |e: String| format!("Line {}: Invalid {} '{}': {}", line_no, key, value, e);
+            match key {
+                "min_step" => {
+                    policy.min_step = value.parse().map_err(|e| bad_value(format!("{}", e)))?
+                }
+                "max_step" => {
+                    policy.max_step = value.parse().map_err(|e| bad_value(format!("{}", e)))?
+                }
+                "allow_plateaus" => {
+                    policy.allow_plateaus =
+                        value.parse().map_err(|e| bad_value(format!("{}", e)))?
+                }
+                "direction" => {
+                    policy.direction = Direction::from_str(value, true).map_err(bad_value)?
+                }
+                _ => return Err(format!("Line {}: Unknown key '{}'", line_no, key)),
+            }
+        }
+        Ok(policy)
+    }
+}
+
+/// Command line flags for building a `SafetyPolicy`. Flags override values from `--policy`.
+#[derive(Args, Debug)]
+pub struct PolicyArgs {
+    /// Config file with `key = value` lines for min_step, max_step, allow_plateaus and direction
+    #[arg(long)]
+    policy: Option<String>,
+    /// Smallest allowed change between adjacent levels
+    #[arg(long)]
+    min_step: Option<i64>,
+    /// Largest allowed change between adjacent levels
+    #[arg(long)]
+    max_step: Option<i64>,
+    /// Allow adjacent levels to be equal
+    #[arg(long)]
+    allow_plateaus: bool,
+    /// Direction the levels have to move in
+    #[arg(long, value_enum)]
+    direction: Option<Direction>,
+}
+
+impl PolicyArgs {
+    pub fn resolve(&self) -> Result<SafetyPolicy, String> {
+        let mut policy = match &self.policy {
+            Some(path) => {
+                let contents = fs::read_to_string(path)
+                    .map_err(|e| format!("Could not read policy file: {}", e))?;
+                SafetyPolicy::parse_config(&contents)?
+            }
+            None => SafetyPolicy::default(),
+        };
+        if let Some(min_step) = self.min_step {
+            policy.min_step = min_step;
+        }
+        if let Some(max_step) = self.max_step {
+            policy.max_step = max_step;
+        }
+        if self.allow_plateaus {
+            policy.allow_plateaus = true;
+        }
+        if let Some(direction) = self.direction {
+            policy.direction = direction;
+        }
+        policy.validate()?;
+        Ok(policy)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Write;
+
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_is_safe_step_default() {
+        let policy = SafetyPolicy::default();
+        assert!(policy.is_safe_step(1, 4, true));
+        assert!(!policy.is_safe_step(1, 5, true));
+        assert!(!policy.is_safe_step(1, 1, true));
+        assert!(!policy.is_safe_step(4, 1, true));
+        assert!(policy.is_safe_step(4, 1, false));
+    }
+
+    #[test]
+    fn test_from_config() {
+        let policy = SafetyPolicy::from_config(
+            "# sensor team B\nmin_step = 2\nmax_step=5\n\nallow_plateaus = true # flat is fine\ndirection = increasing\n",
+        )
+        .unwrap();
+        assert_eq!(
+            policy,
+            SafetyPolicy {
+                min_step: 2,
+                max_step: 5,
+                allow_plateaus: true,
+                direction: Direction::Increasing,
+            }
+        );
+        assert_eq!(policy.directions(), &[true]);
+        assert!(policy.is_safe_step(3, 3, true));
+        assert!(!policy.is_safe_step(3, 4, true));
+    }
+
+    #[test]
+    fn test_from_config_errors() {
+        assert!(SafetyPolicy::from_config("min_step 2").is_err());
+        assert!(SafetyPolicy::from_config("max_steps = 2").is_err());
+        assert!(SafetyPolicy::from_config("direction = sideways").is_err());
+        assert!(SafetyPolicy::from_config("min_step = 4\nmax_step = 3").is_err());
+        assert!(SafetyPolicy::from_config("min_step = 0").is_err());
+    }
+
+    #[test]
+    fn test_policy_args_override_file() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "min_step = 4\ndirection = decreasing").unwrap();
+        let args = PolicyArgs {
+            policy: Some(file.path().to_str().unwrap().to_string()),
+            min_step: None,
+            max_step: Some(7),
+            allow_plateaus: false,
+            direction: None,
+        };
+        let policy = args.resolve().unwrap();
+        assert_eq!(policy.max_step, 7);
+        assert_eq!(policy.direction, Direction::Decreasing);
+        assert_eq!(policy.min_step, 4);
+    }
+}
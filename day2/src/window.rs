@@ -0,0 +1,245 @@
+use std::collections::VecDeque;
+use std::io::{self, BufRead};
+
+use crate::dampener::find_removals;
+use crate::policy::SafetyPolicy;
+
+/// A window changed from safe to unsafe or back. The first full window always
+/// produces one so the starting state is known.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transition {
+    /// Offset of the first reading in the window, counting from 0
+    pub start: usize,
+    /// Offset of the last reading in the window
+    pub end: usize,
+    pub safe: bool,
+}
+
+/// Classifies the last `window` readings every time a new one arrives.
+pub struct WindowClassifier {
+    window: usize,
+    max_removals: usize,
+    policy: SafetyPolicy,
+    levels: VecDeque<i32>,
+    seen: usize,
+    safe: Option<bool>,
+}
+
+impl WindowClassifier {
+    pub fn new(
+        window: usize,
+        max_removals: usize,
+        policy: SafetyPolicy,
+    ) -> Result<WindowClassifier, String> {
+        if window == 0 {
+            return Err("Window length must be at least 1".to_string());
+        }
+        Ok(WindowClassifier {
+            window,
+            max_removals,
+            policy,
+            levels: VecDeque::with_capacity(window),
+            seen: 0,
+            safe: None,
+        })
+    }
+
+    /// Adds a reading and returns a transition if the window's state changed.
+    /// Nothing is classified until the first window is full.
+    pub fn push(&mut self, level: i32) -> Option<Transition> {
+        if self.levels.len() == self.window {
+            self.levels.pop_front();
+        }
+        self.levels.push_back(level);
+        self.seen += 1;
+        if self.levels.len() < self.window {
+            return None;
+        }
+
+        let safe = find_removals(
+            self.levels.make_contiguous(),
+            self.max_removals,
+            &self.policy,
+        )
+        .is_some();
+        if self.safe == Some(safe) {
+            return None;
+        }
+        self.safe = Some(safe);
+        Some(Transition {
+            start: self.seen - self.window,
+            end: self.seen - 1,
+            safe,
+        })
+    }
+
+    /// The state of the latest full window, if there has been one.
+    pub fn is_safe(&self) -> Option<bool> {
+        self.safe
+    }
+}
+
+/// Yields whitespace separated readings as soon as the whitespace after each
+/// one arrives, so nothing waits for a newline or the end of the input.
+pub fn readings<R: BufRead>(reader: R) -> Readings<R> {
+    Readings {
+        reader,
+        token: Vec::new(),
+        done: false,
+    }
+}
+
+pub struct Readings<R> {
+    reader: R,
+    // The bytes of a reading that has started but not yet ended
+    token: Vec<u8>,
+    done: bool,
+}
+
+impl<R> Readings<R> {
+    fn take_token(&mut self) -> Result<i32, String> {
+        let token = String::from_utf8_lossy(&self.token).into_owned();
+        self.token.clear();
+        token
+            .parse()
+            .map_err(|_| format!("Failed to parse '{}'", token))
+    }
+}
+
+impl<R: BufRead> Iterator for Readings<R> {
+    type Item = Result<i32, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.done {
+            let buf = match self.reader.fill_buf() {
+                Ok(buf) => buf,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(format!("Could not read input: {}", e)));
+                }
+            };
+            if buf.is_empty() {
+                self.done = true;
+                break;
+            }
+            match buf.iter().position(|b| b.is_ascii_whitespace()) {
+                Some(end) => {
+                    self.token.extend_from_slice(&buf[..end]);
+                    self.reader.consume(end + 1);
+                    if !self.token.is_empty() {
+                        return Some(self.take_token());
+                    }
+                }
+                None => {
+                    let len = buf.len();
+                    self.token.extend_from_slice(buf);
+                    self.reader.consume(len);
+                }
+            }
+        }
+        // The last reading needs no whitespace after it
+        (!self.token.is_empty()).then(|| self.take_token())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transitions(levels: &[i32], window: usize, max_removals: usize) -> Vec<(usize, bool)> {
+        let mut classifier =
+            WindowClassifier::new(window, max_removals, SafetyPolicy::default()).unwrap();
+        levels
+            .iter()
+            .filter_map(|level| classifier.push(*level))
+            .map(|t| (t.start, t.safe))
+            .collect()
+    }
+
+    #[test]
+    fn test_transitions_without_dampener() {
+        // Every window holding the jump to 9 or to 20 is unsafe
+        let levels = [1, 2, 3, 9, 20, 21, 22];
+        assert_eq!(
+            transitions(&levels, 3, 0),
+            vec![(0, true), (1, false), (4, true)]
+        );
+    }
+
+    #[test]
+    fn test_transitions_with_dampener() {
+        let levels = [1, 2, 3, 9, 20, 21, 22];
+        // Only [3 9 20] needs more than one level removed
+        assert_eq!(
+            transitions(&levels, 3, 1),
+            vec![(0, true), (2, false), (3, true)]
+        );
+    }
+
+    #[test]
+    fn test_transition_offsets() {
+        let mut classifier = WindowClassifier::new(2, 0, SafetyPolicy::default()).unwrap();
+        assert_eq!(classifier.push(5), None);
+        assert_eq!(classifier.is_safe(), None);
+        assert_eq!(
+            classifier.push(6),
+            Some(Transition {
+                start: 0,
+                end: 1,
+                safe: true
+            })
+        );
+        assert_eq!(classifier.push(7), None);
+        assert_eq!(
+            classifier.push(7),
+            Some(Transition {
+                start: 2,
+                end: 3,
+                safe: false
+            })
+        );
+        assert_eq!(classifier.is_safe(), Some(false));
+    }
+
+    #[test]
+    fn test_zero_window() {
+        assert!(WindowClassifier::new(0, 0, SafetyPolicy::default()).is_err());
+    }
+
+    #[test]
+    fn test_readings_without_newline() {
+        let values = readings("7 8\t9".as_bytes()).collect::<Vec<Result<i32, String>>>();
+        assert_eq!(values, vec![Ok(7), Ok(8), Ok(9)]);
+    }
+
+    // Hands out one chunk per read and panics if asked for more, so a test
+    // can check that readings don't wait for input they don't need
+    struct Chunks(Vec<&'static [u8]>);
+
+    impl io::Read for Chunks {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let chunk = self.0.remove(0);
+            buf[..chunk.len()].copy_from_slice(chunk);
+            Ok(chunk.len())
+        }
+    }
+
+    #[test]
+    fn test_readings_stream_without_newline() {
+        let reader = io::BufReader::new(Chunks(vec![b"1 2", b"2 3 ", b"4"]));
+        let mut values = readings(reader);
+        assert_eq!(values.next(), Some(Ok(1)));
+        // 2 may go on in the next chunk, so it is read before 22 is returned
+        assert_eq!(values.next(), Some(Ok(22)));
+        assert_eq!(values.next(), Some(Ok(3)));
+    }
+
+    #[test]
+    fn test_readings() {
+        let input = "1 2\n\n3   4\n5 x\n";
+        let values = readings(input.as_bytes()).collect::<Vec<Result<i32, String>>>();
+        assert_eq!(values[..5], [Ok(1), Ok(2), Ok(3), Ok(4), Ok(5)]);
+        assert!(values[5].is_err());
+    }
+}
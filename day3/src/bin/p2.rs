@@ -0,0 +1,3 @@
+fn main() {
+    day3::cli::run(true);
+}
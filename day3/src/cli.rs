@@ -0,0 +1,67 @@
+use clap::Parser;
+use std::fs::File;
+use std::io::{self, Read};
+
+use crate::instructions::{Syntax, DEFAULT_MAX_DIGITS, EXTENDED, PUZZLE};
+use crate::trace::{open_output, TraceFormat, TraceWriter};
+use crate::{execute_reader, ExecState};
+
+// The command line shared by p1 and p2
+#[derive(Parser)]
+pub struct Args {
+    /// Memory dump to scan; reads stdin when left out
+    file: Option<String>,
+    /// Also run add, sub, div, mod, sum and prod, and allow do(name)/don't(name)
+    #[arg(long)]
+    extended: bool,
+    /// Longest operand allowed; instructions with longer ones are skipped
+    #[arg(
+        long,
+        default_value_t = DEFAULT_MAX_DIGITS,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    max_digits: usize,
+    /// List every instruction and near miss, and why each near miss was rejected
+    #[arg(long, value_enum)]
+    trace: Option<TraceFormat>,
+    /// Write the trace to this file instead of stdout
+    #[arg(long, requires = "trace")]
+    trace_file: Option<String>,
+}
+
+/// Runs p1 or p2, which only differ in whether `do()`/`don't()` count.
+pub fn run(honour_conditionals: bool) {
+    let args = Args::parse();
+    let instructions = if args.extended { EXTENDED } else { PUZZLE };
+    let syntax = Syntax::new(instructions).with_max_digits(args.max_digits);
+
+    let input: Box<dyn Read> = match &args.file {
+        Some(path) => Box::new(File::open(path).expect("Could not open file")),
+        None => Box::new(io::stdin().lock()),
+    };
+
+    let mut state = ExecState::new(syntax, honour_conditionals);
+    if let Some(format) = args.trace {
+        let out = open_output(args.trace_file.as_deref()).expect("Could not write trace");
+        state = state.with_trace(TraceWriter::new(out, format));
+    }
+    let execution =
+        execute_reader(input, state).expect("Could not read the dump or write the trace");
+
+    for skipped in &execution.skipped {
+        eprintln!(
+            "skipped {} at byte {}: {}",
+            skipped.name, skipped.span.start, skipped.reason
+        );
+    }
+    if execution.skipped_count > execution.skipped.len() {
+        eprintln!(
+            "... and {} more skipped",
+            execution.skipped_count - execution.skipped.len()
+        );
+    }
+    // Keep stdout machine readable when a JSON trace is written there
+    if args.trace != Some(TraceFormat::Json) || args.trace_file.is_some() {
+        println!("sum: {}", execution.sum);
+    }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+
+/// What an instruction's arguments look like between the parentheses.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Arity {
+    /// Exactly this many comma separated numbers
+    Exactly(usize),
+    /// This many comma separated numbers or more, up to `MAX_OPERANDS`
+    AtLeast(usize),
+    /// Nothing, or the name of a single flag such as `mul`, no longer than the
+    /// longest instruction name
+    OptionalFlag,
+}
+
+/// Why a complete instruction did not add anything to the sum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SkipReason {
+    /// An operand had more digits than the limit
+    TooManyDigits {
+        max_digits: usize,
+    },
+    /// There were more operands than the limit
+    TooManyOperands {
+        max_operands: usize,
+    },
+    /// An operand does not fit in an i64
+    OperandOverflow,
+    /// The result, or the sum with it added, does not fit in an i64
+    ArithmeticOverflow,
+    DivisionByZero,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::TooManyDigits { max_digits } => {
+                write!(f, "operand has more than {} digits", max_digits)
+            }
+            SkipReason::TooManyOperands { max_operands } => {
+                write!(f, "more than {} operands", max_operands)
+            }
+            SkipReason::OperandOverflow => write!(f, "operand does not fit in 64 bits"),
+            SkipReason::ArithmeticOverflow => write!(f, "result does not fit in 64 bits"),
+            SkipReason::DivisionByZero => write!(f, "division by zero"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Mul,
+    Add,
+    Sub,
+    Div,
+    Mod,
+    Sum,
+    Prod,
+}
+
+impl Op {
+    /// The value the instruction adds to the sum.
+    pub fn apply(&self, args: &[i64]) -> Result<i64, SkipReason> {
+        let checked = |value: Option<i64>| value.ok_or(SkipReason::ArithmeticOverflow);
+        match self {
+            Op::Mul => checked(args[0].checked_mul(args[1])),
+            Op::Add => checked(args[0].checked_add(args[1])),
+            Op::Sub => checked(args[0].checked_sub(args[1])),
+            Op::Div | Op::Mod if args[1] == 0 => Err(SkipReason::DivisionByZero),
+            Op::Div => checked(args[0].checked_div(args[1])),
+            Op::Mod => checked(args[0].checked_rem(args[1])),
+            Op::Sum => checked(args.iter().try_fold(0i64, |acc, a| acc.checked_add(*a))),
+            Op::Prod => checked(args.iter().try_fold(1i64, |acc, a| acc.checked_mul(*a))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Apply(Op),
+    /// Turns a flag on (`true`) or off. Without a flag name it applies to every instruction.
+    Toggle(bool),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstructionSpec {
+    pub name: &'static str,
+    pub arity: Arity,
+    pub action: Action,
+}
+
+const fn spec(name: &'static str, arity: Arity, action: Action) -> InstructionSpec {
+    InstructionSpec {
+        name,
+        arity,
+        action,
+    }
+}
+
+/// The puzzle allows operands of 1-3 digits.
+pub const DEFAULT_MAX_DIGITS: usize = 3;
+
+/// Instructions that take any number of operands are skipped with more than
+/// this many, so an open one never has to keep more.
+pub const MAX_OPERANDS: usize = 64;
+
+/// Which instructions to recognize and how long their operands may be.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Syntax<'a> {
+    pub instructions: &'a [InstructionSpec],
+    pub max_digits: usize,
+}
+
+impl<'a> Syntax<'a> {
+    pub fn new(instructions: &'a [InstructionSpec]) -> Syntax<'a> {
+        Syntax {
+            instructions,
+            max_digits: DEFAULT_MAX_DIGITS,
+        }
+    }
+
+    pub fn with_max_digits(self, max_digits: usize) -> Syntax<'a> {
+        Syntax { max_digits, ..self }
+    }
+}
+
+/// The instructions from the puzzle.
+pub const PUZZLE: &[InstructionSpec] = &[
+    spec("mul", Arity::Exactly(2), Action::Apply(Op::Mul)),
+    spec("do", Arity::Exactly(0), Action::Toggle(true)),
+    spec("don't", Arity::Exactly(0), Action::Toggle(false)),
+];
+
+/// The puzzle instructions plus more arithmetic, and `do`/`don't` that can
+/// name the instruction they apply to, e.g. `don't(add)`.
+pub const EXTENDED: &[InstructionSpec] = &[
+    spec("mul", Arity::Exactly(2), Action::Apply(Op::Mul)),
+    spec("add", Arity::Exactly(2), Action::Apply(Op::Add)),
+    spec("sub", Arity::Exactly(2), Action::Apply(Op::Sub)),
+    spec("div", Arity::Exactly(2), Action::Apply(Op::Div)),
+    spec("mod", Arity::Exactly(2), Action::Apply(Op::Mod)),
+    spec("sum", Arity::AtLeast(1), Action::Apply(Op::Sum)),
+    spec("prod", Arity::AtLeast(1), Action::Apply(Op::Prod)),
+    spec("do", Arity::OptionalFlag, Action::Toggle(true)),
+    spec("don't", Arity::OptionalFlag, Action::Toggle(false)),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        assert_eq!(Op::Sub.apply(&[3, 5]), Ok(-2));
+        assert_eq!(Op::Div.apply(&[7, 2]), Ok(3));
+        assert_eq!(Op::Div.apply(&[7, 0]), Err(SkipReason::DivisionByZero));
+        assert_eq!(Op::Mod.apply(&[7, 0]), Err(SkipReason::DivisionByZero));
+        assert_eq!(Op::Sum.apply(&[1, 2, 3, 4]), Ok(10));
+        assert_eq!(Op::Prod.apply(&[2]), Ok(2));
+    }
+
+    #[test]
+    fn test_apply_overflow() {
+        assert_eq!(
+            Op::Mul.apply(&[i64::MAX, 2]),
+            Err(SkipReason::ArithmeticOverflow)
+        );
+        assert_eq!(
+            Op::Sum.apply(&[i64::MAX, 1, -5]),
+            Err(SkipReason::ArithmeticOverflow)
+        );
+        assert_eq!(Op::Mul.apply(&[999_999, 999_999]), Ok(999_998_000_001));
+    }
+
+    #[test]
+    fn test_names_are_unique() {
+        for table in [PUZZLE, EXTENDED] {
+            for (i, a) in table.iter().enumerate() {
+                assert!(table[i + 1..].iter().all(|b| b.name != a.name));
+            }
+        }
+    }
+}
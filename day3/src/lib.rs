@@ -0,0 +1,445 @@
+pub mod cli;
+pub mod instructions;
+#[cfg(test)]
+mod reference;
+pub mod tokenizer;
+pub mod trace;
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read};
+use std::ops::Range;
+
+use instructions::{SkipReason, Syntax};
+use tokenizer::{Scanned, Scanner, Token};
+use trace::{TraceEntry, TraceWriter};
+
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many skipped instructions an execution keeps; the rest are only counted.
+pub const MAX_SKIPPED: usize = 100;
+
+/// Reads the memory dump as raw bytes; it does not need to be valid UTF-8.
+pub fn read_code(file_path: &str) -> Result<Vec<u8>, String> {
+    fs::read(file_path).map_err(|_| "Could not read file".to_string())
+}
+
+/// A complete instruction that added nothing to the sum.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Skipped {
+    pub name: &'static str,
+    pub span: Range<usize>,
+    pub reason: SkipReason,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Execution {
+    pub sum: i64,
+    /// The first `MAX_SKIPPED` skipped instructions; a trace has all of them
+    pub skipped: Vec<Skipped>,
+    pub skipped_count: usize,
+}
+
+// Evaluation state that carries over from one token to the next
+struct Evaluator<'a> {
+    honour_conditionals: bool,
+    trace: Option<TraceWriter<'a>>,
+    // The first error writing the trace, which ends it
+    trace_error: Option<io::Error>,
+    mul_enabled: bool,
+    disabled: HashSet<String>,
+    execution: Execution,
+}
+
+impl Evaluator<'_> {
+    fn apply(&mut self, scanned: Scanned, text: &[u8]) {
+        let spanned = match scanned {
+            Scanned::Token(spanned) => spanned,
+            Scanned::NearMiss(near_miss) => {
+                if self.trace.is_some() {
+                    self.record(TraceEntry::NearMiss {
+                        offset: near_miss.span.start,
+                        text: String::from_utf8_lossy(text).into_owned(),
+                        reason: near_miss.reason,
+                    });
+                }
+                return;
+            }
+        };
+        let mul_enabled = match &spanned.token {
+            Token::Apply { name, .. } | Token::Skipped { name, .. } => self.enabled(name),
+            Token::Toggle { .. } => self.mul_enabled,
+        };
+        let (contribution, skipped) = self.evaluate(spanned.token);
+        if let Some((name, reason)) = skipped {
+            self.execution.skipped_count += 1;
+            if self.execution.skipped.len() < MAX_SKIPPED {
+                self.execution.skipped.push(Skipped {
+                    name,
+                    span: spanned.span.clone(),
+                    reason,
+                });
+            }
+        }
+        if self.trace.is_some() {
+            self.record(TraceEntry::Instruction {
+                offset: spanned.span.start,
+                text: String::from_utf8_lossy(text).into_owned(),
+                mul_enabled,
+                contribution,
+                skipped: skipped.map(|(_, reason)| reason.to_string()),
+            });
+        }
+    }
+
+    fn record(&mut self, entry: TraceEntry) {
+        if let Some(Err(e)) = self.trace.as_mut().map(|trace| trace.write(&entry)) {
+            self.trace = None;
+            self.trace_error = Some(e);
+        }
+    }
+
+    fn trace_result(&mut self) -> io::Result<()> {
+        match self.trace_error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    fn enabled(&self, name: &str) -> bool {
+        self.mul_enabled && !self.disabled.contains(name)
+    }
+
+    // What the token added to the sum, and why it added nothing if it was skipped
+    fn evaluate(&mut self, token: Token) -> (i64, Option<(&'static str, SkipReason)>) {
+        match token {
+            // A disabled instruction is never run, so it can't be skipped either
+            Token::Apply { name, .. } | Token::Skipped { name, .. } if !self.enabled(name) => {
+                (0, None)
+            }
+            Token::Apply { name, op, args } => {
+                let value = op.apply(&args).and_then(|value| {
+                    self.execution
+                        .sum
+                        .checked_add(value)
+                        .map(|sum| (sum, value))
+                        .ok_or(SkipReason::ArithmeticOverflow)
+                });
+                match value {
+                    Ok((sum, value)) => {
+                        self.execution.sum = sum;
+                        (value, None)
+                    }
+                    Err(reason) => (0, Some((name, reason))),
+                }
+            }
+            Token::Skipped { name, reason } => (0, Some((name, reason))),
+            // Without conditionals every toggle is read but has no effect
+            Token::Toggle { .. } if !self.honour_conditionals => (0, None),
+            Token::Toggle { on, flag: None } => {
+                self.mul_enabled = on;
+                (0, None)
+            }
+            Token::Toggle {
+                on,
+                flag: Some(flag),
+            } => {
+                if on {
+                    self.disabled.remove(&flag);
+                } else {
+                    self.disabled.insert(flag);
+                }
+                (0, None)
+            }
+        }
+    }
+}
+
+/// A resumable execution: feed it the dump in chunks split anywhere, then
+/// `finish` it for the same result `execute` gives on the whole dump.
+pub struct ExecState<'a> {
+    scanner: Scanner<'a>,
+    evaluator: Evaluator<'a>,
+}
+
+impl<'a> ExecState<'a> {
+    /// When `honour_conditionals` is set, `don't()` disables the instructions
+    /// that follow until the next `do()`, and `don't(name)`/`do(name)` do the
+    /// same for just that instruction.
+    pub fn new(syntax: Syntax<'a>, honour_conditionals: bool) -> ExecState<'a> {
+        ExecState {
+            scanner: Scanner::new(syntax),
+            evaluator: Evaluator {
+                honour_conditionals,
+                trace: None,
+                trace_error: None,
+                mul_enabled: true,
+                disabled: HashSet::new(),
+                execution: Execution {
+                    sum: 0,
+                    skipped: Vec::new(),
+                    skipped_count: 0,
+                },
+            },
+        }
+    }
+
+    /// Writes a `TraceEntry` for every instruction and near miss as soon as
+    /// it is read.
+    pub fn with_trace(mut self, trace: TraceWriter<'a>) -> ExecState<'a> {
+        self.evaluator.trace = Some(trace);
+        self
+    }
+
+    /// Only fails when the trace can't be written.
+    pub fn feed(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let evaluator = &mut self.evaluator;
+        self.scanner
+            .feed(chunk, |scanned, text| evaluator.apply(scanned, text));
+        self.evaluator.trace_result()
+    }
+
+    pub fn finish(mut self) -> io::Result<Execution> {
+        let evaluator = &mut self.evaluator;
+        self.scanner
+            .finish(|scanned, text| evaluator.apply(scanned, text));
+        self.evaluator.trace_result()?;
+        if let Some(trace) = self.evaluator.trace {
+            trace.finish()?;
+        }
+        Ok(self.evaluator.execution)
+    }
+}
+
+/// Sums the values of the arithmetic instructions in the whole dump.
+pub fn execute(code: &[u8], syntax: Syntax, honour_conditionals: bool) -> Execution {
+    let mut state = ExecState::new(syntax, honour_conditionals);
+    // Without a trace there is nothing to fail
+    state.feed(code).and_then(|()| state.finish()).unwrap()
+}
+
+/// Runs the execution over a dump of any size from a file or pipe, a chunk at a time.
+pub fn execute_reader<R: Read>(mut reader: R, mut state: ExecState) -> io::Result<Execution> {
+    let mut chunk = vec![0; CHUNK_SIZE];
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => return state.finish(),
+            Ok(n) => state.feed(&chunk[..n])?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use instructions::{InstructionSpec, EXTENDED, PUZZLE};
+    use trace::TraceFormat;
+
+    use std::io::Write;
+
+    use proptest::prelude::*;
+    use tempfile::NamedTempFile;
+
+    fn sum(code: &[u8], instructions: &[InstructionSpec], honour_conditionals: bool) -> i64 {
+        execute(code, Syntax::new(instructions), honour_conditionals).sum
+    }
+
+    #[test]
+    fn test_execute() {
+        for honour_conditionals in [false, true] {
+            let sum = |code: &str| sum(code.as_bytes(), PUZZLE, honour_conditionals);
+            assert_eq!(sum("mul(3,2)"), 6);
+            assert_eq!(sum("asdfaxxmul(3,5mul(3,2)"), 6);
+            assert_eq!(sum("mul(11,2)"), 22);
+            assert_eq!(sum("mul(11,12),3"), 132);
+            assert_eq!(sum("mul(1,2),mul(3,4)"), 14);
+            assert_eq!(sum("mul(1,,2),mul(3,4)"), 12);
+        }
+    }
+
+    #[test]
+    fn test_execute_conditionals() {
+        assert_eq!(sum(b"mul(1,2)don't()", PUZZLE, true), 2);
+        assert_eq!(sum(b"don't()mul(1,2)", PUZZLE, true), 0);
+        assert_eq!(sum(b"mul(1,2)don't(3,4)do()dontmul(5,6)", PUZZLE, true), 32);
+        assert_eq!(sum(b"don't()mul(1,2)", PUZZLE, false), 2);
+    }
+
+    #[test]
+    fn test_execute_extended() {
+        let code = "add(1,2)sub(1,5)div(9,2)div(1,0)mod(9,4)sum(1,2,3)prod(2,3,4)";
+        assert_eq!(sum(code.as_bytes(), EXTENDED, true), 3 - 4 + 4 + 1 + 6 + 24);
+        // Only the puzzle's mul counts without --extended
+        assert_eq!(sum(b"add(1,2)mul(2,2)", PUZZLE, true), 4);
+    }
+
+    #[test]
+    fn test_execute_named_flags() {
+        let code = "don't(mul)mul(2,2)add(1,1)don't()add(5,5)do()do(mul)mul(3,3)";
+        assert_eq!(sum(code.as_bytes(), EXTENDED, true), 2 + 9);
+        assert_eq!(sum(code.as_bytes(), EXTENDED, false), 4 + 2 + 10 + 9);
+    }
+
+    #[test]
+    fn test_read_code_non_utf8() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"\x00\xffmul(2,3)\xc3\x28don't()mul(4,4)")
+            .unwrap();
+        let code = read_code(file.path().to_str().unwrap()).unwrap();
+        assert_eq!(sum(&code, PUZZLE, false), 22);
+        assert_eq!(sum(&code, PUZZLE, true), 6);
+    }
+
+    #[test]
+    fn test_execute_reports_skipped() {
+        let code = b"mul(1000,2)mul(2,3)div(4,0)don't()mul(1111,1)div(1,0)";
+        let execution = execute(code, Syntax::new(EXTENDED), true);
+        assert_eq!(execution.sum, 6);
+        assert_eq!(
+            execution.skipped,
+            vec![
+                Skipped {
+                    name: "mul",
+                    span: 0..11,
+                    reason: SkipReason::TooManyDigits { max_digits: 3 }
+                },
+                Skipped {
+                    name: "div",
+                    span: 19..27,
+                    reason: SkipReason::DivisionByZero
+                },
+            ]
+        );
+        // Without conditionals nothing is disabled, so both are reported
+        let execution = execute(code, Syntax::new(EXTENDED), false);
+        assert_eq!(execution.skipped.len(), 4);
+    }
+
+    #[test]
+    fn test_execute_counts_skipped_past_the_limit() {
+        let code = "mul(1,1)div(1,0)".repeat(MAX_SKIPPED + 50);
+        let execution = execute(code.as_bytes(), Syntax::new(EXTENDED), true);
+        assert_eq!(execution.skipped.len(), MAX_SKIPPED);
+        assert_eq!(execution.skipped_count, MAX_SKIPPED + 50);
+        assert_eq!(execution.sum, MAX_SKIPPED as i64 + 50);
+    }
+
+    #[test]
+    fn test_execute_sum_overflow() {
+        let syntax = Syntax::new(PUZZLE).with_max_digits(19);
+        let code = b"mul(9223372036854775807,1)mul(1,1)mul(2,2)";
+        let execution = execute(code, syntax, false);
+        assert_eq!(execution.sum, i64::MAX);
+        assert_eq!(execution.skipped.len(), 2);
+        assert_eq!(execution.skipped[0].reason, SkipReason::ArithmeticOverflow);
+    }
+
+    #[test]
+    fn test_execute_reader_stdin_sized_chunks() {
+        let code = "mul(2,3)don't()mul(4,5)do()mul(7,8)".repeat(5000);
+        let state = ExecState::new(Syntax::new(PUZZLE), true);
+        let execution = execute_reader(code.as_bytes(), state).unwrap();
+        assert_eq!(
+            execution,
+            execute(code.as_bytes(), Syntax::new(PUZZLE), true)
+        );
+        assert_eq!(execution.sum, 5000 * 62);
+    }
+
+    // The execution and its trace, fed the chunks one after another
+    fn traced<'c>(
+        chunks: impl IntoIterator<Item = &'c [u8]>,
+        syntax: Syntax,
+        honour_conditionals: bool,
+        format: TraceFormat,
+    ) -> (Execution, String) {
+        let mut out = Vec::new();
+        let mut state = ExecState::new(syntax, honour_conditionals)
+            .with_trace(TraceWriter::new(&mut out, format));
+        for chunk in chunks {
+            state.feed(chunk).unwrap();
+        }
+        let execution = state.finish().unwrap();
+        (execution, String::from_utf8(out).unwrap())
+    }
+
+    // Fails every write, like a closed pipe
+    struct Broken;
+
+    impl Write for Broken {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_trace_is_written_as_it_is_read() {
+        let mut state = ExecState::new(Syntax::new(PUZZLE), true)
+            .with_trace(TraceWriter::new(Broken, TraceFormat::Text));
+        state.feed(b"mul(1,").unwrap();
+        let error = state.feed(b"2)mul(3,").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::BrokenPipe);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_chunk_splits_give_same_result(
+            code in reference::corrupted_memory(),
+            cuts in proptest::collection::vec(any::<prop::sample::Index>(), 0..8),
+            extended in any::<bool>(),
+            honour_conditionals in any::<bool>(),
+        ) {
+            let syntax = Syntax::new(if extended { EXTENDED } else { PUZZLE });
+            // Tracing too, so near misses have to agree as well
+            let traced = |chunks: Vec<&[u8]>| traced(chunks, syntax, honour_conditionals, TraceFormat::Json);
+            let expected = traced(vec![&code]);
+
+            let mut cuts = cuts.iter().map(|i| i.index(code.len() + 1)).collect::<Vec<usize>>();
+            cuts.push(0);
+            cuts.push(code.len());
+            cuts.sort();
+            let chunks = cuts.windows(2).map(|w| &code[w[0]..w[1]]).collect();
+            prop_assert_eq!(traced(chunks), expected.clone());
+
+            // One byte at a time is the worst split
+            prop_assert_eq!(traced(code.chunks(1).collect()), expected);
+        }
+    }
+
+    #[test]
+    fn test_trace() {
+        let code = b"xmul(2,4)don't()mul(5,5)mul(1,,2)do()mul(1000,1)";
+        let (execution, trace) = traced([&code[..]], Syntax::new(PUZZLE), true, TraceFormat::Text);
+        assert_eq!(
+            trace.lines().collect::<Vec<&str>>(),
+            vec![
+                "       1 mul(2,4)         enabled  +8",
+                "       9 don't()          enabled  +0",
+                "      16 mul(5,5)         disabled +0",
+                "      24 mul(1,,          rejected: expected a digit, found ','",
+                "      33 do()             disabled +0",
+                "      37 mul(1000,1)      enabled  +0 (skipped: operand has more than 3 digits)",
+            ]
+        );
+        // Tracing doesn't change the result
+        assert_eq!(execution.sum, execute(code, Syntax::new(PUZZLE), true).sum);
+
+        // Disabled by name while everything else is enabled
+        let code = b"don't(mul)mul(2,2)add(1,1)";
+        let (_, trace) = traced([&code[..]], Syntax::new(EXTENDED), true, TraceFormat::Text);
+        assert_eq!(
+            trace.lines().collect::<Vec<&str>>(),
+            vec![
+                "       0 don't(mul)       enabled  +0",
+                "      10 mul(2,2)         disabled +0",
+                "      18 add(1,1)         enabled  +2",
+            ]
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! A deliberately simple second implementation of the puzzle grammar, used
+//! to check the tokenizer, and a generator of corrupted memory for both.
+//!
+//! The differential test only checks about a thousand dumps by default. The
+//! ignored long run checks about two million:
+//! `cargo test --release differential_long_run -- --ignored`. proptest shrinks
+//! any disagreement to a minimal dump before reporting it.
+
+use proptest::prelude::*;
+use regex::bytes::Regex;
+use std::ops::Range;
+use std::sync::LazyLock;
+
+use crate::execute;
+use crate::instructions::{Syntax, DEFAULT_MAX_DIGITS, PUZZLE};
+use crate::tokenizer::{tokenize, Token};
+
+#[derive(Debug, Clone, PartialEq)]
+enum RefToken {
+    /// None when an operand is too long to count
+    Mul(Option<(i64, i64)>),
+    Do,
+    Dont,
+}
+
+// Leftmost-first, non-overlapping regex matches are exactly the puzzle's rule
+// for which instructions count
+static INSTRUCTION: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"mul\(([0-9]+),([0-9]+)\)|do\(\)|don't\(\)").unwrap());
+
+fn reference_tokens(code: &[u8]) -> Vec<(Range<usize>, RefToken)> {
+    INSTRUCTION
+        .captures_iter(code)
+        .map(|caps| {
+            let m = caps.get(0).unwrap();
+            let token = match m.as_bytes() {
+                b"do()" => RefToken::Do,
+                b"don't()" => RefToken::Dont,
+                _ => {
+                    let operand = |i| {
+                        let digits = caps.get(i).unwrap().as_bytes();
+                        std::str::from_utf8(digits)
+                            .unwrap()
+                            .parse::<i64>()
+                            .ok()
+                            .filter(|_| digits.len() <= DEFAULT_MAX_DIGITS)
+                    };
+                    RefToken::Mul(operand(1).zip(operand(2)))
+                }
+            };
+            (m.range(), token)
+        })
+        .collect()
+}
+
+fn reference_sum(code: &[u8], honour_conditionals: bool) -> i64 {
+    let mut enabled = true;
+    let mut sum = 0;
+    for (_, token) in reference_tokens(code) {
+        match token {
+            RefToken::Mul(Some((a, b))) if enabled => sum += a * b,
+            RefToken::Mul(_) => {}
+            RefToken::Do => enabled = true,
+            RefToken::Dont => enabled = !honour_conditionals,
+        }
+    }
+    sum
+}
+
+fn tokenizer_tokens(code: &[u8]) -> Vec<(Range<usize>, RefToken)> {
+    tokenize(code, Syntax::new(PUZZLE))
+        .map(|spanned| {
+            let token = match spanned.token {
+                Token::Apply { args, .. } => RefToken::Mul(Some((args[0], args[1]))),
+                Token::Skipped { .. } => RefToken::Mul(None),
+                Token::Toggle { on: true, .. } => RefToken::Do,
+                Token::Toggle { on: false, .. } => RefToken::Dont,
+            };
+            (spanned.span, token)
+        })
+        .collect()
+}
+
+/// Dumps built from pieces of real and broken instructions mixed with noise,
+/// so that near misses and restarts in the middle of an instruction are common.
+pub fn corrupted_memory() -> impl Strategy<Value = Vec<u8>> {
+    let piece = prop_oneof![
+        Just(b"mul(".to_vec()),
+        ("[0-9]{1,5}", "[0-9]{1,5}").prop_map(|(a, b)| format!("mul({},{})", a, b).into_bytes()),
+        Just(b"do()".to_vec()),
+        Just(b"don't()".to_vec()),
+        Just(b"don't(mul)".to_vec()),
+        Just(b"sum(".to_vec()),
+        Just("é".as_bytes().to_vec()),
+        proptest::collection::vec(b'0'..=b'9', 1..6),
+        proptest::collection::vec(
+            prop_oneof![
+                Just(b'm'),
+                Just(b'u'),
+                Just(b'l'),
+                Just(b'd'),
+                Just(b'o'),
+                Just(b'n'),
+                Just(b'\''),
+                Just(b't'),
+                Just(b'('),
+                Just(b')'),
+                Just(b','),
+                b'0'..=b'9',
+                any::<u8>(),
+            ],
+            1..4,
+        ),
+    ];
+    proptest::collection::vec(piece, 0..40).prop_map(|pieces| pieces.concat())
+}
+
+#[test]
+fn test_reference_examples() {
+    let code = b"xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+    assert_eq!(reference_sum(code, false), 161);
+    assert_eq!(reference_sum(code, true), 48);
+    assert_eq!(reference_tokens(b"mul(1234,5)")[0].1, RefToken::Mul(None));
+}
+
+fn check_against_reference(dumps: &[Vec<u8>]) -> Result<(), TestCaseError> {
+    for code in dumps {
+        prop_assert_eq!(tokenizer_tokens(code), reference_tokens(code));
+        for honour_conditionals in [false, true] {
+            prop_assert_eq!(
+                execute(code, Syntax::new(PUZZLE), honour_conditionals).sum,
+                reference_sum(code, honour_conditionals)
+            );
+        }
+    }
+    Ok(())
+}
+
+proptest! {
+    #[test]
+    fn differential_tokenizer_matches_reference(
+        dumps in proptest::collection::vec(corrupted_memory(), 1..8)
+    ) {
+        check_against_reference(&dumps)?;
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig {
+        cases: 500_000,
+        ..ProptestConfig::default()
+    })]
+
+    #[test]
+    #[ignore = "long run, see the module docs"]
+    fn differential_long_run(
+        dumps in proptest::collection::vec(corrupted_memory(), 1..8)
+    ) {
+        check_against_reference(&dumps)?;
+    }
+}
@@ -0,0 +1,994 @@
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::instructions::{Action, Arity, InstructionSpec, Op, SkipReason, Syntax, MAX_OPERANDS};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    /// An arithmetic instruction whose value is added to the sum
+    Apply {
+        name: &'static str,
+        op: Op,
+        args: Vec<i64>,
+    },
+    /// `do`/`don't`, optionally naming the instruction it applies to
+    Toggle { on: bool, flag: Option<String> },
+    /// A complete arithmetic instruction whose operands can't be used
+    Skipped {
+        name: &'static str,
+        reason: SkipReason,
+    },
+}
+
+/// A token and the byte range of its text in the dump, e.g. all of `mul(3,4)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned {
+    pub token: Token,
+    pub span: Range<usize>,
+}
+
+/// Text that started out as an instruction, with its name and opening
+/// parenthesis, but went wrong after that, e.g. `mul(1,,2)` or `mul(3,5`.
+/// The span runs up to and including the byte that broke it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NearMiss {
+    pub name: &'static str,
+    pub span: Range<usize>,
+    pub reason: String,
+}
+
+/// Everything the scanner reports, in input order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Scanned {
+    Token(Spanned),
+    NearMiss(NearMiss),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Reason {
+    /// Describes what would have continued the instruction
+    Expected(&'static str),
+    /// The exact text that would have continued it
+    Literal(&'static str),
+    Arguments {
+        arity: Arity,
+        found: usize,
+    },
+    /// Another operand started after the last one the instruction takes
+    ExtraArgument(Arity),
+}
+
+// Why there is no instruction at a position. Incomplete means the bytes so far
+// could still become one once more input arrives, and says how far the
+// operands got if it ran out while reading them.
+#[derive(Debug, Clone, PartialEq)]
+enum Miss {
+    NoMatch(Reason),
+    Incomplete(Option<Progress>),
+}
+
+// The operands of an open instruction that were read before the input ran
+// out, and where the next one starts, so the next chunk carries on from there
+// instead of reading the instruction again from its start
+#[derive(Debug, Clone, PartialEq)]
+struct Progress {
+    pos: usize,
+    numbers: Vec<Range<usize>>,
+    // Operands past the first MAX_OPERANDS, which are counted but not kept
+    dropped: usize,
+}
+
+impl Progress {
+    // The same progress with positions counted from `to` instead of `from`
+    fn rebase(self, from: usize, to: usize) -> Progress {
+        let at = |i: usize| i - from + to;
+        Progress {
+            pos: at(self.pos),
+            numbers: self
+                .numbers
+                .into_iter()
+                .map(|r| at(r.start)..at(r.end))
+                .collect(),
+            dropped: self.dropped,
+        }
+    }
+}
+
+// Reads the bytes after an instruction name, giving up on the first byte that
+// doesn't fit the instruction's arity
+struct Cursor<'a> {
+    code: &'a [u8],
+    pos: usize,
+    // Whether the end of `code` is the end of the input
+    at_end: bool,
+}
+
+impl<'a> Cursor<'a> {
+    fn ran_out(&self, reason: Reason) -> Miss {
+        if self.at_end {
+            Miss::NoMatch(reason)
+        } else {
+            Miss::Incomplete(None)
+        }
+    }
+
+    fn peek(&self, expected: &'static str) -> Result<u8, Miss> {
+        self.code
+            .get(self.pos)
+            .copied()
+            .ok_or_else(|| self.ran_out(Reason::Expected(expected)))
+    }
+
+    fn eat(&mut self, expected: &'static str) -> Result<(), Miss> {
+        let rest = &self.code[self.pos..];
+        if rest.starts_with(expected.as_bytes()) {
+            self.pos += expected.len();
+            return Ok(());
+        }
+        if expected.as_bytes().starts_with(rest) {
+            return Err(self.ran_out(Reason::Literal(expected)));
+        }
+        Err(Miss::NoMatch(Reason::Literal(expected)))
+    }
+
+    // The run has to be followed by another byte, or it might continue in the
+    // next chunk. It stops after `max` bytes whatever follows.
+    fn take_while(
+        &mut self,
+        f: impl Fn(u8) -> bool,
+        expected: &'static str,
+        max: usize,
+    ) -> Result<Range<usize>, Miss> {
+        let start = self.pos;
+        while self.pos - start < max && f(self.peek(expected)?) {
+            self.pos += 1;
+        }
+        Ok(start..self.pos)
+    }
+
+    // Comma separated digit runs up to and including the closing parenthesis,
+    // after the `numbers` already read and the ones `dropped` past them. Gives
+    // up as soon as an operand starts after the `most` there can be.
+    fn numbers(
+        &mut self,
+        mut numbers: Vec<Range<usize>>,
+        mut dropped: usize,
+        arity: Arity,
+    ) -> Result<(Vec<Range<usize>>, usize), Miss> {
+        let most = match arity {
+            Arity::Exactly(n) => n,
+            _ => usize::MAX,
+        };
+        if numbers.is_empty() && self.peek("a digit or ')'")? == b')' {
+            self.pos += 1;
+            return Ok((numbers, dropped));
+        }
+        loop {
+            let start = self.pos;
+            if numbers.len() + dropped >= most {
+                return Err(Miss::NoMatch(Reason::ExtraArgument(arity)));
+            }
+            let digits = self.take_while(|b| b.is_ascii_digit(), "a digit, ',' or ')'", usize::MAX);
+            let digits = match digits {
+                Ok(digits) => digits,
+                // Only a digit run can run out, so the operands before it are done
+                Err(Miss::Incomplete(_)) => {
+                    return Err(Miss::Incomplete(Some(Progress {
+                        pos: start,
+                        numbers,
+                        dropped,
+                    })))
+                }
+                Err(miss) => return Err(miss),
+            };
+            if digits.is_empty() {
+                return Err(Miss::NoMatch(Reason::Expected("a digit")));
+            }
+            if numbers.len() < MAX_OPERANDS {
+                numbers.push(digits);
+            } else {
+                dropped += 1;
+            }
+            match self.peek("',' or ')'")? {
+                b',' => self.pos += 1,
+                b')' => {
+                    self.pos += 1;
+                    return Ok((numbers, dropped));
+                }
+                _ => return Err(Miss::NoMatch(Reason::Expected("',' or ')'"))),
+            }
+        }
+    }
+
+    // An optional instruction name up to and including the closing parenthesis.
+    // A flag longer than `max_len` could never name an instruction.
+    fn flag(&mut self, max_len: usize) -> Result<Option<String>, Miss> {
+        let flag = self.take_while(
+            |b| b.is_ascii_lowercase() || b == b'_' || b == b'\'',
+            "a flag name or ')'",
+            max_len,
+        )?;
+        let flag =
+            (!flag.is_empty()).then(|| String::from_utf8_lossy(&self.code[flag]).into_owned());
+        self.eat(")")?;
+        Ok(flag)
+    }
+
+    fn describe(&self, name: &str, reason: Reason) -> String {
+        let found = || match self.code.get(self.pos) {
+            Some(b) => format!("'{}'", b.escape_ascii()),
+            None => "the end of the input".to_string(),
+        };
+        match reason {
+            Reason::Expected(expected) => format!("expected {}, found {}", expected, found()),
+            Reason::Literal(expected) => format!("expected '{}', found {}", expected, found()),
+            Reason::Arguments { arity, found } => {
+                format!(
+                    "{} takes {} arguments, found {}",
+                    name,
+                    wanted(arity),
+                    found
+                )
+            }
+            Reason::ExtraArgument(arity) => {
+                format!("{} takes {} arguments, found more", name, wanted(arity))
+            }
+        }
+    }
+}
+
+fn wanted(arity: Arity) -> String {
+    match arity {
+        Arity::Exactly(n) => n.to_string(),
+        Arity::AtLeast(n) => format!("at least {}", n),
+        Arity::OptionalFlag => "no".to_string(),
+    }
+}
+
+// Runs that are too long or too big skip the instruction rather than
+// ending the scan, since the rest of the dump is still worth reading
+fn operands(
+    code: &[u8],
+    digits: &[Range<usize>],
+    dropped: usize,
+    max_digits: usize,
+) -> Result<Vec<i64>, SkipReason> {
+    if dropped > 0 {
+        return Err(SkipReason::TooManyOperands {
+            max_operands: MAX_OPERANDS,
+        });
+    }
+    if digits.iter().any(|d| d.len() > max_digits) {
+        return Err(SkipReason::TooManyDigits { max_digits });
+    }
+    digits
+        .iter()
+        .map(|d| {
+            // Only ASCII digits were taken, so this is valid UTF-8
+            let d = std::str::from_utf8(&code[d.clone()]).unwrap();
+            d.parse::<i64>().map_err(|_| SkipReason::OperandOverflow)
+        })
+        .collect()
+}
+
+fn parse_spec(
+    cursor: &mut Cursor,
+    spec: &InstructionSpec,
+    syntax: Syntax,
+    progress: Option<Progress>,
+) -> Result<Token, Miss> {
+    let (numbers, dropped) = match progress {
+        // Already past the name and some of the operands
+        Some(progress) => {
+            cursor.pos = progress.pos;
+            (progress.numbers, progress.dropped)
+        }
+        None => {
+            cursor.eat(spec.name)?;
+            cursor.eat("(")?;
+            (Vec::new(), 0)
+        }
+    };
+    let token = match (spec.action, spec.arity) {
+        (Action::Toggle(on), Arity::OptionalFlag) => {
+            let longest = syntax.instructions.iter().map(|s| s.name.len()).max();
+            Token::Toggle {
+                on,
+                flag: cursor.flag(longest.unwrap_or(0))?,
+            }
+        }
+        (Action::Toggle(on), _) => {
+            cursor.eat(")")?;
+            Token::Toggle { on, flag: None }
+        }
+        (Action::Apply(op), arity) => {
+            let (args, dropped) = cursor.numbers(numbers, dropped, arity)?;
+            let found = args.len() + dropped;
+            let fits = match arity {
+                Arity::Exactly(n) => found == n,
+                Arity::AtLeast(n) => found >= n,
+                Arity::OptionalFlag => false,
+            };
+            if !fits {
+                return Err(Miss::NoMatch(Reason::Arguments { arity, found }));
+            }
+            match operands(cursor.code, &args, dropped, syntax.max_digits) {
+                Ok(args) => Token::Apply {
+                    name: spec.name,
+                    op,
+                    args,
+                },
+                Err(reason) => Token::Skipped {
+                    name: spec.name,
+                    reason,
+                },
+            }
+        }
+    };
+    Ok(token)
+}
+
+enum Parsed {
+    Token(Spanned),
+    NoMatch(Option<NearMiss>),
+    /// With the instruction's index and progress, counted from its start, if
+    /// it got as far as its operands
+    Incomplete(Option<(usize, Progress)>),
+}
+
+// Names are never a prefix of another name plus '(', so at most one
+// instruction can match at a position, and only one can have got as far as
+// its operands. `resume` carries on with that one.
+fn parse_at(
+    code: &[u8],
+    start: usize,
+    syntax: Syntax,
+    at_end: bool,
+    mut resume: Option<(usize, Progress)>,
+) -> Parsed {
+    let mut incomplete = None;
+    let mut near_miss: Option<NearMiss> = None;
+    for (index, spec) in syntax.instructions.iter().enumerate() {
+        if resume
+            .as_ref()
+            .is_some_and(|(resumed, _)| *resumed != index)
+        {
+            continue;
+        }
+        let progress = resume.take().map(|(_, p)| p.rebase(0, start));
+        let mut cursor = Cursor {
+            code,
+            pos: start,
+            at_end,
+        };
+        match parse_spec(&mut cursor, spec, syntax, progress) {
+            Ok(token) => {
+                return Parsed::Token(Spanned {
+                    token,
+                    span: start..cursor.pos,
+                })
+            }
+            Err(Miss::Incomplete(progress)) => {
+                if progress.is_some() || incomplete.is_none() {
+                    incomplete = Some(progress.map(|p| (index, p.rebase(start, 0))));
+                }
+            }
+            // Only once the name and '(' were read is it worth reporting
+            Err(Miss::NoMatch(reason)) if cursor.pos > start + spec.name.len() => {
+                let end = match reason {
+                    Reason::Expected(_) | Reason::Literal(_) => (cursor.pos + 1).min(code.len()),
+                    Reason::Arguments { .. } | Reason::ExtraArgument(_) => cursor.pos,
+                };
+                if near_miss.as_ref().is_none_or(|n| end > n.span.end) {
+                    near_miss = Some(NearMiss {
+                        name: spec.name,
+                        span: start..end,
+                        reason: cursor.describe(spec.name, reason),
+                    });
+                }
+            }
+            Err(Miss::NoMatch(_)) => {}
+        }
+    }
+    match incomplete {
+        Some(progress) => Parsed::Incomplete(progress),
+        None => Parsed::NoMatch(near_miss),
+    }
+}
+
+/// Yields the valid instructions in a raw memory dump, skipping everything
+/// else. The dump does not have to be UTF-8.
+///
+/// An instruction can start at any byte, so after a partial match fails the
+/// scan carries on from the byte after the one that started it.
+pub struct Tokenizer<'a> {
+    code: &'a [u8],
+    syntax: Syntax<'a>,
+    pos: usize,
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(code: &'a [u8], syntax: Syntax<'a>) -> Tokenizer<'a> {
+        Tokenizer {
+            code,
+            syntax,
+            pos: 0,
+        }
+    }
+}
+
+impl Iterator for Tokenizer<'_> {
+    type Item = Spanned;
+
+    fn next(&mut self) -> Option<Spanned> {
+        while self.pos < self.code.len() {
+            if let Parsed::Token(spanned) = parse_at(self.code, self.pos, self.syntax, true, None) {
+                self.pos = spanned.span.end;
+                return Some(spanned);
+            }
+            self.pos += 1;
+        }
+        None
+    }
+}
+
+pub fn tokenize<'a>(code: &'a [u8], syntax: Syntax<'a>) -> Tokenizer<'a> {
+    Tokenizer::new(code, syntax)
+}
+
+/// Tokenizes input that arrives in chunks, giving the same tokens and spans
+/// as `tokenize` on the whole input however it is split. Near misses are
+/// reported too, along with the raw bytes of each item.
+///
+/// Only the bytes of an instruction that is still being read are kept between
+/// chunks, so memory use depends on the longest instruction, not the input.
+/// An operand can never count once it has more than `max_digits` digits, so
+/// only that many and one more are kept, and the item's text shows the rest as
+/// `...`. Likewise only the first `MAX_OPERANDS` operands are kept and shown.
+/// Flags are no longer than the longest instruction name.
+pub struct Scanner<'a> {
+    syntax: Syntax<'a>,
+    pending: Vec<u8>,
+    // Offset of pending[0] in the whole input, not counting elided digits
+    offset: usize,
+    // How far the instruction at pending[0] got, when it ran out in its operands
+    resume: Option<(usize, Progress)>,
+    // Digits dropped from overlong operands, and operands dropped past
+    // MAX_OPERANDS: the index in pending they were dropped before, and how many
+    elided: Vec<(usize, usize)>,
+}
+
+impl<'a> Scanner<'a> {
+    pub fn new(syntax: Syntax<'a>) -> Scanner<'a> {
+        Scanner {
+            syntax,
+            pending: Vec::new(),
+            offset: 0,
+            resume: None,
+            elided: Vec::new(),
+        }
+    }
+
+    pub fn feed(&mut self, chunk: &[u8], emit: impl FnMut(Scanned, &[u8])) {
+        self.pending.extend_from_slice(chunk);
+        self.scan(false, emit);
+    }
+
+    /// Ends the input, emitting nothing for an instruction that was cut off.
+    pub fn finish(mut self, emit: impl FnMut(Scanned, &[u8])) {
+        self.scan(true, emit);
+    }
+
+    // Where a range of pending is in the whole input
+    fn span(&self, range: &Range<usize>) -> Range<usize> {
+        let at = |i: usize| {
+            let elided = self.elided.iter().filter(|(before, _)| *before <= i);
+            self.offset + i + elided.map(|(_, n)| n).sum::<usize>()
+        };
+        at(range.start)..at(range.end)
+    }
+
+    // The text of a range of pending, with every run of more than
+    // max_digits + 1 digits cut to that many and `...`, and every list of more
+    // than MAX_OPERANDS operands cut to that many and `,...`, whether or not
+    // the rest were elided already
+    fn text(&self, range: Range<usize>) -> Cow<'_, [u8]> {
+        let keep = self.syntax.max_digits.saturating_add(1);
+        let bytes = &self.pending[range.clone()];
+        let cut = |i: usize| {
+            self.elided
+                .iter()
+                .any(|(before, _)| *before == range.start + i)
+        };
+        let long = bytes
+            .split(|b| !b.is_ascii_digit())
+            .any(|run| run.len() > keep);
+        let many = bytes
+            .split(|b| !b.is_ascii_digit() && *b != b',')
+            .any(|list| list.iter().filter(|b| **b == b',').count() >= MAX_OPERANDS);
+        let elided = self
+            .elided
+            .iter()
+            .any(|(before, _)| range.start < *before && *before < range.end);
+        if !long && !many && !elided {
+            return Cow::Borrowed(bytes);
+        }
+        let mut text = Vec::with_capacity(bytes.len());
+        let mut run = 0;
+        // Which operand of a list the byte is in
+        let mut operand = 0;
+        for (i, &b) in bytes.iter().enumerate() {
+            if i > 0 && cut(i) && operand < MAX_OPERANDS {
+                run = keep + 1;
+                text.extend_from_slice(b"...");
+            }
+            if b.is_ascii_digit() {
+                run += 1;
+            } else {
+                run = 0;
+                operand = if b == b',' { operand + 1 } else { 0 };
+            }
+            if operand >= MAX_OPERANDS {
+                if b == b',' && operand == MAX_OPERANDS {
+                    text.extend_from_slice(b",...");
+                }
+            } else if run <= keep {
+                text.push(b);
+            } else if run == keep + 1 {
+                text.extend_from_slice(b"...");
+            }
+        }
+        Cow::Owned(text)
+    }
+
+    fn scan(&mut self, at_end: bool, mut emit: impl FnMut(Scanned, &[u8])) {
+        let mut pos = 0;
+        while pos < self.pending.len() {
+            // Only the instruction at the start can have been read before
+            let resume = if pos == 0 { self.resume.take() } else { None };
+            match parse_at(&self.pending, pos, self.syntax, at_end, resume) {
+                Parsed::Token(spanned) => {
+                    let span = self.span(&spanned.span);
+                    let text = self.text(spanned.span.clone());
+                    pos = spanned.span.end;
+                    emit(Scanned::Token(Spanned { span, ..spanned }), &text);
+                }
+                Parsed::NoMatch(near_miss) => {
+                    if let Some(near_miss) = near_miss {
+                        let span = self.span(&near_miss.span);
+                        let text = self.text(near_miss.span.clone());
+                        emit(Scanned::NearMiss(NearMiss { span, ..near_miss }), &text);
+                    }
+                    pos += 1;
+                }
+                Parsed::Incomplete(progress) => {
+                    self.resume = progress;
+                    break;
+                }
+            }
+        }
+        self.offset = self.span(&(pos..pos)).start;
+        self.elided.retain_mut(|(before, _)| {
+            *before = before.saturating_sub(pos);
+            *before > 0
+        });
+        self.pending.drain(..pos);
+
+        // The open instruction is reading an operand up to the end of pending.
+        // Past MAX_OPERANDS the operands before it are only counted, and so
+        // are the digits past the ones kept once it is too long to count,
+        // however long either goes on for.
+        if let Some((_, progress)) = &mut self.resume {
+            if progress.dropped > 0 {
+                // Just after the comma that ends the last operand kept
+                let cut = progress.numbers.last().map_or(0, |r| r.end + 1);
+                if progress.pos > cut {
+                    let mut dropped = progress.pos - cut;
+                    self.pending.drain(cut..progress.pos);
+                    self.elided.retain(|&(before, n)| {
+                        let inside = cut <= before && before <= progress.pos;
+                        if inside {
+                            dropped += n;
+                        }
+                        !inside
+                    });
+                    self.elided.push((cut, dropped));
+                    progress.pos = cut;
+                }
+            }
+            let keep = self.syntax.max_digits.saturating_add(1);
+            let cut = progress.pos.saturating_add(keep);
+            if self.pending.len() > cut {
+                let dropped = self.pending.len() - cut;
+                self.pending.truncate(cut);
+                match self.elided.last_mut() {
+                    Some((before, n)) if *before == cut => *n += dropped,
+                    _ => self.elided.push((cut, dropped)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::{EXTENDED, PUZZLE};
+
+    fn mul(a: i64, b: i64) -> Token {
+        Token::Apply {
+            name: "mul",
+            op: Op::Mul,
+            args: vec![a, b],
+        }
+    }
+
+    fn toggle(on: bool, flag: Option<&str>) -> Token {
+        Token::Toggle {
+            on,
+            flag: flag.map(|f| f.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_tokens_and_spans() {
+        let code = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let spanned = tokenize(code.as_bytes(), Syntax::new(PUZZLE)).collect::<Vec<Spanned>>();
+        let tokens = spanned
+            .iter()
+            .map(|s| s.token.clone())
+            .collect::<Vec<Token>>();
+        assert_eq!(
+            tokens,
+            vec![
+                mul(2, 4),
+                toggle(false, None),
+                mul(5, 5),
+                mul(11, 8),
+                toggle(true, None),
+                mul(8, 5),
+            ]
+        );
+        for s in &spanned {
+            assert!(code[s.span.clone()].ends_with(')'));
+        }
+        assert_eq!(spanned[0].span, 1..9);
+        assert_eq!(&code[spanned[1].span.clone()], "don't()");
+        assert_eq!(&code[spanned[4].span.clone()], "do()");
+    }
+
+    #[test]
+    fn test_restart_inside_instruction() {
+        let spanned = tokenize(b"mumul(1,2)dodo()", Syntax::new(PUZZLE)).collect::<Vec<Spanned>>();
+        assert_eq!(spanned[0].span, 2..10);
+        assert_eq!(spanned[1].span, 12..16);
+    }
+
+    #[test]
+    fn test_puzzle_set_rejects_extensions() {
+        let tokens = tokenize(b"add(1,2)don't(mul)sum(1)mul(1,2,3)", Syntax::new(PUZZLE)).count();
+        assert_eq!(tokens, 0);
+    }
+
+    #[test]
+    fn test_extended_tokens() {
+        let code = "sum(1,2,3)prod()don't(mul)do()add(4,5)mod(7,3)div(1)do(mul)sum()";
+        let tokens = tokenize(code.as_bytes(), Syntax::new(EXTENDED))
+            .map(|s| s.token)
+            .collect::<Vec<Token>>();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Apply {
+                    name: "sum",
+                    op: Op::Sum,
+                    args: vec![1, 2, 3]
+                },
+                toggle(false, Some("mul")),
+                toggle(true, None),
+                Token::Apply {
+                    name: "add",
+                    op: Op::Add,
+                    args: vec![4, 5]
+                },
+                Token::Apply {
+                    name: "mod",
+                    op: Op::Mod,
+                    args: vec![7, 3]
+                },
+                toggle(true, Some("mul")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_failed_flag_rescans_its_text() {
+        // `do(mul` is not a toggle, but the mul inside it still counts
+        let tokens = tokenize(b"do(mul(2,3)", Syntax::new(EXTENDED))
+            .map(|s| s.token)
+            .collect::<Vec<Token>>();
+        assert_eq!(tokens, vec![mul(2, 3)]);
+    }
+
+    #[test]
+    fn test_multibyte_noise() {
+        // Byte offsets, not char offsets: é is 2 bytes and 日本 is 6
+        let code = "é mul(2,3)日本don't()mul(é4,5)mul(6,7)";
+        let spanned = tokenize(code.as_bytes(), Syntax::new(PUZZLE)).collect::<Vec<Spanned>>();
+        assert_eq!(spanned.len(), 3);
+        assert_eq!(spanned[0].span, 3..11);
+        assert_eq!(&code[spanned[0].span.clone()], "mul(2,3)");
+        assert_eq!(&code[spanned[1].span.clone()], "don't()");
+        assert_eq!(&code[spanned[2].span.clone()], "mul(6,7)");
+        assert_eq!(spanned[2].token, mul(6, 7));
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let code = b"\xffmul(1,2)\xc3mul(\x803,4)\xe6\x97mul(5,6)\xfe";
+        let spanned = tokenize(code, Syntax::new(PUZZLE)).collect::<Vec<Spanned>>();
+        assert_eq!(
+            spanned
+                .iter()
+                .map(|s| s.token.clone())
+                .collect::<Vec<Token>>(),
+            vec![mul(1, 2), mul(5, 6)]
+        );
+        assert_eq!(spanned[0].span, 1..9);
+        assert_eq!(&code[spanned[1].span.clone()], b"mul(5,6)");
+    }
+
+    #[test]
+    fn test_operand_limits() {
+        let code = b"mul(1234,5)mul(123,45)mul(12345678901234567890,1)mul(1234";
+        let tokens = |syntax| {
+            tokenize(code, syntax)
+                .map(|s| s.token)
+                .collect::<Vec<Token>>()
+        };
+        assert_eq!(
+            tokens(Syntax::new(PUZZLE)),
+            vec![
+                Token::Skipped {
+                    name: "mul",
+                    reason: SkipReason::TooManyDigits { max_digits: 3 }
+                },
+                mul(123, 45),
+                Token::Skipped {
+                    name: "mul",
+                    reason: SkipReason::TooManyDigits { max_digits: 3 }
+                },
+            ]
+        );
+        assert_eq!(
+            tokens(Syntax::new(PUZZLE).with_max_digits(30)),
+            vec![
+                mul(1234, 5),
+                mul(123, 45),
+                Token::Skipped {
+                    name: "mul",
+                    reason: SkipReason::OperandOverflow
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_keeps_only_the_open_instruction() {
+        let mut scanner = Scanner::new(Syntax::new(PUZZLE));
+        let mut spanned = Vec::new();
+        scanner.feed(b"noise noise mu", |s, _| spanned.push(s));
+        assert_eq!(scanner.pending, b"mu");
+        scanner.feed(b"l(1,", |s, _| spanned.push(s));
+        scanner.feed(b"2)do(", |s, _| spanned.push(s));
+        assert_eq!(scanner.pending, b"do(");
+        scanner.finish(|s, _| spanned.push(s));
+        assert_eq!(
+            spanned,
+            vec![
+                Scanned::Token(Spanned {
+                    token: mul(1, 2),
+                    span: 12..20
+                }),
+                // Cut off by the end of the input
+                Scanned::NearMiss(NearMiss {
+                    name: "do",
+                    span: 20..23,
+                    reason: "expected ')', found the end of the input".to_string()
+                }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_bounds_long_operand() {
+        let mut scanner = Scanner::new(Syntax::new(PUZZLE));
+        let mut found = Vec::new();
+        let mut record = |scanned, text: &[u8]| {
+            found.push((scanned, String::from_utf8_lossy(text).into_owned()));
+        };
+        scanner.feed(b"xmul(1,", &mut record);
+        for _ in 0..1000 {
+            scanner.feed(&[b'7'; 100], &mut record);
+            // The name, the first operand and max_digits + 1 digits
+            assert!(scanner.pending.len() <= "mul(1,7777".len());
+        }
+        scanner.feed(b")mul(2,3)", &mut record);
+        scanner.finish(&mut record);
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Scanned::Token(Spanned {
+                        token: Token::Skipped {
+                            name: "mul",
+                            reason: SkipReason::TooManyDigits { max_digits: 3 }
+                        },
+                        span: 1..100_008
+                    }),
+                    "mul(1,7777...)".to_string()
+                ),
+                (
+                    Scanned::Token(Spanned {
+                        token: mul(2, 3),
+                        span: 100_008..100_016
+                    }),
+                    "mul(2,3)".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scanner_bounds_long_operand_list() {
+        let mut scanner = Scanner::new(Syntax::new(EXTENDED));
+        let mut found = Vec::new();
+        let mut record = |scanned, text: &[u8]| {
+            found.push((scanned, String::from_utf8_lossy(text).into_owned()));
+        };
+        scanner.feed(b"xsum(", &mut record);
+        for _ in 0..100_000 {
+            scanner.feed(b"1,", &mut record);
+            // The name and MAX_OPERANDS operands, and the one being read
+            assert!(scanner.pending.len() <= "sum(".len() + 2 * MAX_OPERANDS + 2);
+        }
+        scanner.feed(b"1)mul(1,2,3)", &mut record);
+        scanner.finish(&mut record);
+        let list = format!("sum({}...)", "1,".repeat(MAX_OPERANDS));
+        assert_eq!(
+            found,
+            vec![
+                (
+                    Scanned::Token(Spanned {
+                        token: Token::Skipped {
+                            name: "sum",
+                            reason: SkipReason::TooManyOperands {
+                                max_operands: MAX_OPERANDS
+                            }
+                        },
+                        span: 1..200_007
+                    }),
+                    list.clone()
+                ),
+                (
+                    Scanned::NearMiss(NearMiss {
+                        name: "mul",
+                        span: 200_007..200_015,
+                        reason: "mul takes 2 arguments, found more".to_string()
+                    }),
+                    "mul(1,2,".to_string()
+                ),
+            ]
+        );
+
+        // The whole input shows the same text
+        let code = format!("sum({}1)", "1,".repeat(1000));
+        let mut scanner = Scanner::new(Syntax::new(EXTENDED));
+        let mut texts = Vec::new();
+        scanner.feed(code.as_bytes(), |_, text| {
+            texts.push(String::from_utf8_lossy(text).into_owned())
+        });
+        assert_eq!(texts, vec![list]);
+
+        // However it is split, with long operands among the kept ones and the
+        // dropped ones, and a list that goes wrong after them
+        let code = format!(
+            "sum({}1)prod({}2,x",
+            "12345,".repeat(70),
+            "1,".repeat(MAX_OPERANDS - 2) + "123456,"
+        );
+        let scan = |size: usize| {
+            let mut scanner = Scanner::new(Syntax::new(EXTENDED));
+            let mut found = Vec::new();
+            let mut record = |scanned, text: &[u8]| {
+                found.push((scanned, String::from_utf8_lossy(text).into_owned()));
+            };
+            for chunk in code.as_bytes().chunks(size) {
+                scanner.feed(chunk, &mut record);
+            }
+            scanner.finish(&mut record);
+            found
+        };
+        let whole = scan(code.len());
+        assert_eq!(whole.len(), 2);
+        for size in [1, 2, 3, 7, 100] {
+            assert_eq!(scan(size), whole);
+        }
+
+        // Past its last operand an instruction is dropped straight away
+        let mut scanner = Scanner::new(Syntax::new(PUZZLE));
+        scanner.feed(b"mul(", |_, _| {});
+        for _ in 0..1000 {
+            scanner.feed(b"1,", |_, _| {});
+            assert!(scanner.pending.len() <= "mul(1,".len());
+        }
+    }
+
+    #[test]
+    fn test_flag_longer_than_any_name() {
+        let tokens = tokenize(b"don't(abcde)don't(abcdef)", Syntax::new(EXTENDED))
+            .map(|s| s.token)
+            .collect::<Vec<Token>>();
+        assert_eq!(tokens, vec![toggle(false, Some("abcde"))]);
+
+        let mut scanner = Scanner::new(Syntax::new(EXTENDED));
+        let mut near_misses = Vec::new();
+        scanner.feed(b"do(", |_, _| {});
+        for _ in 0..1000 {
+            scanner.feed(b"z", |s, _| near_misses.push(s));
+        }
+        assert!(scanner.pending.len() < 10);
+        assert_eq!(
+            near_misses,
+            vec![Scanned::NearMiss(NearMiss {
+                name: "do",
+                span: 0..9,
+                reason: "expected ')', found 'z'".to_string()
+            })]
+        );
+    }
+
+    fn near_misses(code: &[u8]) -> Vec<(String, String)> {
+        let mut found = Vec::new();
+        let mut scanner = Scanner::new(Syntax::new(PUZZLE));
+        let mut record = |scanned, text: &[u8]| {
+            if let Scanned::NearMiss(near_miss) = scanned {
+                found.push((String::from_utf8_lossy(text).into_owned(), near_miss.reason));
+            }
+        };
+        scanner.feed(code, &mut record);
+        scanner.finish(&mut record);
+        found
+    }
+
+    #[test]
+    fn test_near_misses() {
+        assert_eq!(
+            near_misses(b"mul(1,,2)mul(3,5mul[2,2]do(x)mul(4,5,6)"),
+            vec![
+                (
+                    "mul(1,,".to_string(),
+                    "expected a digit, found ','".to_string()
+                ),
+                (
+                    "mul(3,5m".to_string(),
+                    "expected ',' or ')', found 'm'".to_string()
+                ),
+                ("do(x".to_string(), "expected ')', found 'x'".to_string()),
+                (
+                    "mul(4,5,".to_string(),
+                    "mul takes 2 arguments, found more".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            near_misses(b"xmul(12"),
+            vec![(
+                "mul(12".to_string(),
+                "expected a digit, ',' or ')', found the end of the input".to_string()
+            )]
+        );
+        // A good instruction is never also a near miss
+        assert!(near_misses(b"don't()do()mul(1,2)").is_empty());
+    }
+}
@@ -0,0 +1,184 @@
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum TraceFormat {
+    Text,
+    Json,
+}
+
+/// One line of the trace. Offsets are in bytes from the start of the dump and
+/// text is the raw instruction, with invalid UTF-8 replaced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TraceEntry {
+    Instruction {
+        offset: usize,
+        text: String,
+        /// Whether this instruction was enabled when it was read, by `do()`/`don't()`
+        /// and, for an arithmetic instruction, `do(name)`/`don't(name)`
+        mul_enabled: bool,
+        contribution: i64,
+        /// Why a complete instruction added nothing even though it was enabled
+        skipped: Option<String>,
+    },
+    NearMiss {
+        offset: usize,
+        text: String,
+        reason: String,
+    },
+}
+
+impl TraceEntry {
+    pub fn line(&self) -> String {
+        match self {
+            TraceEntry::Instruction {
+                offset,
+                text,
+                mul_enabled,
+                contribution,
+                skipped,
+            } => {
+                let mut line = format!(
+                    "{:>8} {:<16} {:<8} {:+}",
+                    offset,
+                    text,
+                    if *mul_enabled { "enabled" } else { "disabled" },
+                    contribution
+                );
+                if let Some(reason) = skipped {
+                    line.push_str(&format!(" (skipped: {})", reason));
+                }
+                line
+            }
+            TraceEntry::NearMiss {
+                offset,
+                text,
+                reason,
+            } => format!("{:>8} {:<16} rejected: {}", offset, text, reason),
+        }
+    }
+}
+
+/// The named file, or stdout when there is none.
+pub fn open_output(path: Option<&str>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+/// Writes trace entries as they are produced, so a trace of any length never
+/// has to be held in memory. JSON is a single array with an entry per line.
+pub struct TraceWriter<'a> {
+    out: Box<dyn Write + 'a>,
+    format: TraceFormat,
+    written: usize,
+}
+
+impl<'a> TraceWriter<'a> {
+    pub fn new(out: impl Write + 'a, format: TraceFormat) -> TraceWriter<'a> {
+        TraceWriter {
+            out: Box::new(out),
+            format,
+            written: 0,
+        }
+    }
+
+    pub fn write(&mut self, entry: &TraceEntry) -> io::Result<()> {
+        match self.format {
+            TraceFormat::Text => writeln!(self.out, "{}", entry.line())?,
+            TraceFormat::Json => {
+                self.out.write_all(if self.written == 0 {
+                    b"[\n  "
+                } else {
+                    b",\n  "
+                })?;
+                serde_json::to_writer(&mut self.out, entry)?;
+            }
+        }
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Closes the JSON array and flushes the output.
+    pub fn finish(mut self) -> io::Result<()> {
+        if self.format == TraceFormat::Json {
+            self.out
+                .write_all(if self.written == 0 { b"[]\n" } else { b"\n]\n" })?;
+        }
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries() -> Vec<TraceEntry> {
+        vec![
+            TraceEntry::Instruction {
+                offset: 1,
+                text: "mul(2,4)".to_string(),
+                mul_enabled: true,
+                contribution: 8,
+                skipped: None,
+            },
+            TraceEntry::NearMiss {
+                offset: 9,
+                text: "mul(3,5m".to_string(),
+                reason: "expected ',' or ')', found 'm'".to_string(),
+            },
+        ]
+    }
+
+    fn write_trace(out: &mut Vec<u8>, entries: &[TraceEntry], format: TraceFormat) {
+        let mut writer = TraceWriter::new(out, format);
+        for entry in entries {
+            writer.write(entry).unwrap();
+        }
+        writer.finish().unwrap();
+    }
+
+    #[test]
+    fn test_write_trace_text() {
+        let mut out = Vec::new();
+        write_trace(&mut out, &entries(), TraceFormat::Text);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "       1 mul(2,4)         enabled  +8\n       \
+             9 mul(3,5m         rejected: expected ',' or ')', found 'm'\n"
+        );
+    }
+
+    #[test]
+    fn test_write_trace_json() {
+        let mut out = Vec::new();
+        write_trace(&mut out, &entries(), TraceFormat::Json);
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(
+            parsed[0],
+            serde_json::json!({
+                "kind": "instruction",
+                "offset": 1,
+                "text": "mul(2,4)",
+                "mul_enabled": true,
+                "contribution": 8,
+                "skipped": null,
+            })
+        );
+        assert_eq!(parsed[1]["kind"], "near_miss");
+        assert_eq!(parsed[1]["reason"], "expected ',' or ')', found 'm'");
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 4);
+    }
+
+    #[test]
+    fn test_write_trace_json_empty() {
+        let mut out = Vec::new();
+        write_trace(&mut out, &[], TraceFormat::Json);
+        let parsed: serde_json::Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(parsed, serde_json::json!([]));
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, VecDeque};
+
+/// An Aho-Corasick automaton that finds every word of a dictionary in one
+/// pass over a sequence, including words that overlap or contain each other.
+/// Letters can be bytes, chars or anything else that can be ordered.
+#[derive(Debug, Clone)]
+pub struct WordAutomaton<T = u8> {
+    goto: Vec<BTreeMap<T, usize>>,
+    fail: Vec<usize>,
+    // Words ending at each state, including those reached through fail links
+    out: Vec<Vec<usize>>,
+    lengths: Vec<usize>,
+}
+
+const ROOT: usize = 0;
+
+impl<T: Clone + Ord> WordAutomaton<T> {
+    /// Empty words are ignored; they would match everywhere.
+    pub fn new<W: AsRef<[T]>>(words: &[W]) -> WordAutomaton<T> {
+        let mut automaton = WordAutomaton {
+            goto: vec![BTreeMap::new()],
+            fail: vec![ROOT],
+            out: vec![Vec::new()],
+            lengths: words.iter().map(|w| w.as_ref().len()).collect(),
+        };
+        for (index, word) in words.iter().enumerate() {
+            let word = word.as_ref();
+            if word.is_empty() {
+                continue;
+            }
+            let mut state = ROOT;
+            for b in word {
+                state = match automaton.goto[state].get(b) {
+                    Some(&next) => next,
+                    None => {
+                        let next = automaton.goto.len();
+                        automaton.goto.push(BTreeMap::new());
+                        automaton.fail.push(ROOT);
+                        automaton.out.push(Vec::new());
+                        automaton.goto[state].insert(b.clone(), next);
+                        next
+                    }
+                };
+            }
+            automaton.out[state].push(index);
+        }
+
+        // Breadth first, so a state's fail target is always finished before it
+        let mut queue = automaton.goto[ROOT]
+            .values()
+            .copied()
+            .collect::<VecDeque<usize>>();
+        while let Some(state) = queue.pop_front() {
+            let edges = automaton.goto[state]
+                .iter()
+                .map(|(b, &next)| (b.clone(), next))
+                .collect::<Vec<(T, usize)>>();
+            for (b, next) in edges {
+                let fail = automaton.step(automaton.fail[state], &b);
+                automaton.fail[next] = fail;
+                let inherited = automaton.out[fail].clone();
+                automaton.out[next].extend(inherited);
+                queue.push_back(next);
+            }
+        }
+        automaton
+    }
+
+    pub fn start(&self) -> usize {
+        ROOT
+    }
+
+    pub fn step(&self, mut state: usize, b: &T) -> usize {
+        loop {
+            if let Some(&next) = self.goto[state].get(b) {
+                return next;
+            }
+            if state == ROOT {
+                return ROOT;
+            }
+            state = self.fail[state];
+        }
+    }
+
+    /// Indices of the words that end at this state.
+    pub fn matches(&self, state: usize) -> &[usize] {
+        &self.out[state]
+    }
+
+    pub fn word_len(&self, word: usize) -> usize {
+        self.lengths[word]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find_all(words: &[&str], text: &str) -> Vec<(usize, usize)> {
+        let automaton = WordAutomaton::new(words);
+        let mut state = automaton.start();
+        let mut found = Vec::new();
+        for (i, b) in text.bytes().enumerate() {
+            state = automaton.step(state, &b);
+            for &word in automaton.matches(state) {
+                found.push((i + 1 - automaton.word_len(word), word));
+            }
+        }
+        found.sort();
+        found
+    }
+
+    #[test]
+    fn test_overlapping_words() {
+        let words = ["he", "she", "his", "hers"];
+        assert_eq!(find_all(&words, "ushers"), vec![(1, 1), (2, 0), (2, 3)]);
+    }
+
+    #[test]
+    fn test_repeated_and_empty_words() {
+        assert_eq!(find_all(&["", "AA"], "AAAA"), vec![(0, 1), (1, 1), (2, 1)]);
+        assert_eq!(find_all(&["XMAS", "XMAS"], "XMAS"), vec![(0, 0), (0, 1)]);
+    }
+}
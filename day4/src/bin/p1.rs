@@ -0,0 +1,137 @@
+use clap::Parser;
+use std::fs;
+use std::io::Write;
+
+use day4::board::{read_board, Cell, CellKind};
+use day4::paths::{find_paths, parse_moves, PathSearch, Walk, KING_MOVES};
+use day4::render::{mark_cells, open_output, write_board, Highlight};
+use day4::search::{count_by_word, find_words};
+
+#[derive(Parser)]
+struct Args {
+    file: String,
+    /// File with one word to search for per line, instead of just XMAS
+    #[arg(long)]
+    words: Option<String>,
+    /// What one cell of the board and one letter of a word is
+    #[arg(long, value_enum, default_value_t = CellKind::Bytes)]
+    cells: CellKind,
+    /// Print every match with its start cell and direction, or its path
+    #[arg(long)]
+    list: bool,
+    /// Let words run off one edge of the board and back on at the other
+    #[arg(long)]
+    wrap: bool,
+    /// Steps between letters: `king`, `knight` or a list such as `0,1;1,0`
+    #[arg(long)]
+    moves: Option<String>,
+    /// Keep to one step for the whole word, or take any step for each letter
+    #[arg(long, value_enum)]
+    walk: Option<Walk>,
+    /// Print the board with the matched cells picked out
+    #[arg(long, value_enum)]
+    show: Option<Highlight>,
+    /// Write the board to this file instead of stdout
+    #[arg(long, requires = "show")]
+    show_file: Option<String>,
+}
+
+fn read_words(path: &str) -> Result<Vec<String>, String> {
+    let contents =
+        fs::read_to_string(path).map_err(|e| format!("Could not read word list: {}", e))?;
+    let words = contents
+        .lines()
+        .map(|line| line.trim().to_string())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<String>>();
+    if words.is_empty() {
+        return Err("No words found in word list".to_string());
+    }
+    Ok(words)
+}
+
+// Runs the search and any listing and board output, returning the count for
+// each word
+fn search<T: Cell>(args: &Args, words: &[String]) -> Vec<usize> {
+    let board = read_board::<T>(&args.file).unwrap();
+    let letters = words
+        .iter()
+        .map(|word| T::split(word.as_bytes()).unwrap())
+        .collect::<Vec<Vec<T>>>();
+
+    // Straight lines in the eight directions use the faster line search
+    let (counts, cells) = if args.wrap || args.moves.is_some() || args.walk.is_some() {
+        let search = PathSearch {
+            moves: match &args.moves {
+                Some(spec) => parse_moves(spec).unwrap(),
+                None => KING_MOVES.to_vec(),
+            },
+            wrap: args.wrap,
+            walk: args.walk.unwrap_or(Walk::Straight),
+        };
+        let matches = find_paths(&board, &letters, &search);
+        let mut counts = vec![0; words.len()];
+        for m in &matches {
+            counts[m.word] += 1;
+            if args.list {
+                let path = m
+                    .path
+                    .iter()
+                    .map(|(row, col)| format!("({}, {})", row, col))
+                    .collect::<Vec<String>>();
+                println!("{} along {}", words[m.word], path.join(" "));
+            }
+        }
+        let cells = matches.into_iter().flat_map(|m| m.path).collect::<Vec<_>>();
+        (counts, cells)
+    } else {
+        let matches = find_words(&board, &letters);
+        if args.list {
+            for m in &matches {
+                println!(
+                    "{} at row {}, column {} going {}",
+                    words[m.word], m.start.0, m.start.1, m.direction
+                );
+            }
+        }
+        let cells = matches
+            .iter()
+            .flat_map(|m| m.cells(letters[m.word].len()))
+            .collect::<Vec<_>>();
+        (count_by_word(&matches, words.len()), cells)
+    };
+
+    if let Some(highlight) = args.show {
+        let mask = mark_cells(board.dim(), cells);
+        open_output(args.show_file.as_deref())
+            .and_then(|mut out| {
+                write_board(&mut out, &board, &mask, highlight)?;
+                out.flush()
+            })
+            .expect("Could not write board");
+    }
+    counts
+}
+
+fn main() {
+    let args = Args::parse();
+    let words = match &args.words {
+        Some(path) => read_words(path).unwrap(),
+        None => vec!["XMAS".to_string()],
+    };
+
+    let counts = match args.cells {
+        CellKind::Bytes => search::<u8>(&args, &words),
+        CellKind::Chars => search::<char>(&args, &words),
+        CellKind::Graphemes => search::<String>(&args, &words),
+    };
+    let total = counts.iter().sum::<usize>();
+    if args.words.is_none() {
+        println!("xmas count {}", total);
+        return;
+    }
+    for (word, count) in words.iter().zip(counts) {
+        println!("{} count {}", word, count);
+    }
+    println!("total {}", total);
+}
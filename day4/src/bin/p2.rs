@@ -0,0 +1,73 @@
+use clap::Parser;
+use std::io::Write;
+
+use day4::board::read_puzzle_board;
+use day4::render::{mark_cells, open_output, write_board, Highlight};
+use day4::stencil::{count_by_stencil, find_stencils, read_stencils, x_mas};
+
+#[derive(Parser)]
+struct Args {
+    file: String,
+    /// File of stencils separated by blank lines, instead of just the X-MAS
+    #[arg(long)]
+    stencils: Option<String>,
+    /// Character in the stencils file that matches any letter
+    #[arg(long, default_value_t = '.', requires = "stencils")]
+    wildcard: char,
+    /// Print every match with its orientation and top left cell
+    #[arg(long)]
+    list: bool,
+    /// Print the board with the matched cells picked out
+    #[arg(long, value_enum)]
+    show: Option<Highlight>,
+    /// Write the board to this file instead of stdout
+    #[arg(long, requires = "show")]
+    show_file: Option<String>,
+}
+
+fn main() {
+    let args = Args::parse();
+    if !args.wildcard.is_ascii() || args.wildcard.is_ascii_alphanumeric() {
+        eprintln!("The wildcard must be an ASCII character other than a letter or digit");
+        std::process::exit(1);
+    }
+    let wildcard = args.wildcard as u8;
+    let board = read_puzzle_board(&args.file).unwrap();
+    let stencils = match &args.stencils {
+        Some(path) => read_stencils(path, wildcard).unwrap(),
+        None => vec![x_mas()],
+    };
+
+    let matches = find_stencils(&board, &stencils);
+    if let Some(highlight) = args.show {
+        let mask = mark_cells(board.dim(), matches.iter().flat_map(|m| m.cells(&stencils)));
+        open_output(args.show_file.as_deref())
+            .and_then(|mut out| {
+                write_board(&mut out, &board, &mask, highlight)?;
+                out.flush()
+            })
+            .expect("Could not write board");
+    }
+    if args.list {
+        for m in &matches {
+            println!(
+                "stencil {} ({}) at row {}, column {}",
+                m.stencil + 1,
+                m.orientation,
+                m.start.0,
+                m.start.1
+            );
+        }
+    }
+    if args.stencils.is_none() {
+        println!("xmas count {}", matches.len());
+        return;
+    }
+    for (index, count) in count_by_stencil(&matches, stencils.len())
+        .iter()
+        .enumerate()
+    {
+        println!("stencil {} count {}", index + 1, count);
+    }
+    println!("total {}", matches.len());
+}
@@ -0,0 +1,176 @@
+use clap::ValueEnum;
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::str::{self, Utf8Error};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// What one cell of the board holds.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CellKind {
+    /// One byte, which is all an ASCII puzzle needs
+    Bytes,
+    /// One Unicode scalar value
+    Chars,
+    /// One user-perceived character, e.g. a letter with combining accents
+    Graphemes,
+}
+
+/// A letter on the board or in a word.
+pub trait Cell: Clone + Ord {
+    /// Splits a line of the board, or a word, into cells.
+    fn split(line: &[u8]) -> Result<Vec<Self>, Utf8Error>;
+    /// Appends the cell's UTF-8 to `out`, or its byte for byte cells.
+    fn encode(&self, out: &mut Vec<u8>);
+}
+
+impl Cell for u8 {
+    fn split(line: &[u8]) -> Result<Vec<u8>, Utf8Error> {
+        Ok(line.to_vec())
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.push(*self);
+    }
+}
+
+impl Cell for char {
+    fn split(line: &[u8]) -> Result<Vec<char>, Utf8Error> {
+        Ok(str::from_utf8(line)?.chars().collect())
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.encode_utf8(&mut [0; 4]).as_bytes());
+    }
+}
+
+impl Cell for String {
+    fn split(line: &[u8]) -> Result<Vec<String>, Utf8Error> {
+        Ok(str::from_utf8(line)?
+            .graphemes(true)
+            .map(str::to_string)
+            .collect())
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        out.extend_from_slice(self.as_bytes());
+    }
+}
+
+pub fn read_puzzle_board(path: &str) -> io::Result<Array2<u8>> {
+    read_board(path)
+}
+
+/// Reads a board of any kind of cell. Every line must have the same number
+/// of cells, which for chars and graphemes is not the number of bytes.
+pub fn read_board<T: Cell>(path: &str) -> io::Result<Array2<T>> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::new(file);
+    let mut lines = Vec::new();
+    let mut cols = None;
+
+    let mut buffer = Vec::new();
+    while reader.read_until(b'\n', &mut buffer)? > 0 {
+        let line_no = lines.len() + 1;
+        let last_char_is_newline = buffer.ends_with(b"\n");
+        if last_char_is_newline {
+            buffer.pop();
+        }
+        let cells = T::split(&buffer).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Invalid UTF-8 on line {}: {}", line_no, e),
+            )
+        })?;
+        match cols {
+            None => cols = Some(cells.len()),
+            Some(cols) if cells.len() != cols => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "Invalid number of columns ({}) on line {}, expected {}",
+                        cells.len(),
+                        line_no,
+                        cols
+                    ),
+                ));
+            }
+            Some(_) => {}
+        }
+
+        lines.push(cells);
+        buffer.clear();
+    }
+    if lines.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "No lines found in file",
+        ));
+    }
+
+    let board = Array2::from_shape_vec(
+        (lines.len(), cols.unwrap()),
+        lines.into_iter().flatten().collect(),
+    )
+    .unwrap();
+
+    Ok(board)
+}
+
+/// A byte board from rows of the same length, for tests.
+#[cfg(test)]
+pub(crate) fn board(rows: &[&str]) -> Array2<u8> {
+    Array2::from_shape_vec(
+        (rows.len(), rows[0].len()),
+        rows.iter().flat_map(|r| r.bytes()).collect(),
+    )
+    .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    fn board_file(text: &str) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(text.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_columns_count_chars() {
+        let file = board_file("éte\nabc\n");
+        let path = file.path().to_str().unwrap();
+        let board = read_board::<char>(path).unwrap();
+        assert_eq!(board.dim(), (2, 3));
+        assert_eq!(board[[0, 0]], 'é');
+        // As bytes the first line is one longer than the second
+        let err = read_puzzle_board(path).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Invalid number of columns (3) on line 2, expected 4"
+        );
+    }
+
+    #[test]
+    fn test_graphemes() {
+        // An e followed by a combining acute accent is two chars but one grapheme
+        let file = board_file("e\u{301}x\nab\n");
+        let path = file.path().to_str().unwrap();
+        let board = read_board::<String>(path).unwrap();
+        assert_eq!(board[[0, 0]], "e\u{301}");
+        assert!(read_board::<char>(path).is_err());
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(b"ab\n\xffc\n").unwrap();
+        let path = file.path().to_str().unwrap();
+        let err = read_board::<char>(path).unwrap_err();
+        assert!(err.to_string().starts_with("Invalid UTF-8 on line 2"));
+        assert!(read_puzzle_board(path).is_ok());
+    }
+}
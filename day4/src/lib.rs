@@ -0,0 +1,6 @@
+pub mod automaton;
+pub mod board;
+pub mod paths;
+pub mod render;
+pub mod search;
+pub mod stencil;
@@ -0,0 +1,288 @@
+use clap::ValueEnum;
+use ndarray::Array2;
+
+use crate::search::offset;
+
+/// A (row, column) change from one letter to the next.
+pub type Step = (isize, isize);
+
+/// One step in any of the eight directions.
+pub const KING_MOVES: [Step; 8] = [
+    (-1, 0),
+    (1, 0),
+    (0, -1),
+    (0, 1),
+    (-1, -1),
+    (-1, 1),
+    (1, -1),
+    (1, 1),
+];
+
+pub const KNIGHT_MOVES: [Step; 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+
+/// Reads `king`, `knight` or a list of steps such as `0,1;1,0`.
+pub fn parse_moves(spec: &str) -> Result<Vec<Step>, String> {
+    match spec {
+        "king" => return Ok(KING_MOVES.to_vec()),
+        "knight" => return Ok(KNIGHT_MOVES.to_vec()),
+        _ => {}
+    }
+    let mut moves = Vec::new();
+    for step in spec.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        let parsed = step
+            .split_once(',')
+            .and_then(|(dr, dc)| Some((dr.trim().parse().ok()?, dc.trim().parse().ok()?)));
+        match parsed {
+            Some((0, 0)) => return Err("A step of 0,0 never leaves its cell".to_string()),
+            Some(step) if !moves.contains(&step) => moves.push(step),
+            Some(_) => {}
+            None => return Err(format!("Failed to parse step '{}'", step)),
+        }
+    }
+    if moves.is_empty() {
+        return Err("No steps given".to_string());
+    }
+    Ok(moves)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Walk {
+    /// Every letter is the same step on from the one before
+    Straight,
+    /// Every letter is any of the steps on from the one before, as in Boggle
+    Boggle,
+}
+
+/// How words may be laid out on the board.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSearch {
+    pub moves: Vec<Step>,
+    /// Steps off one edge come back on at the opposite one
+    pub wrap: bool,
+    pub walk: Walk,
+}
+
+/// One occurrence of a word: its index in the word list and the cells of its
+/// letters in order. No cell is used twice in a path.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PathMatch {
+    pub word: usize,
+    pub path: Vec<(usize, usize)>,
+    /// The step between letters in a straight walk, or each step a single
+    /// letter is read along. None for a longer word in a Boggle walk.
+    pub step: Option<Step>,
+}
+
+impl PathSearch {
+    fn next(
+        &self,
+        cell: (usize, usize),
+        step: Step,
+        shape: (usize, usize),
+    ) -> Option<(usize, usize)> {
+        if !self.wrap {
+            return offset(cell, step, shape);
+        }
+        // Reducing the step first keeps a huge one from overflowing
+        let wrapped = |i: usize, d: isize, n: usize| (i + d.rem_euclid(n as isize) as usize) % n;
+        Some((
+            wrapped(cell.0, step.0, shape.0),
+            wrapped(cell.1, step.1, shape.1),
+        ))
+    }
+
+    // Extends the path one letter at a time, trying every step in Boggle mode
+    // and only the first step taken otherwise
+    fn extend<T: PartialEq>(
+        &self,
+        board: &Array2<T>,
+        word: usize,
+        rest: &[T],
+        path: &mut Vec<(usize, usize)>,
+        step: Option<Step>,
+        found: &mut Vec<PathMatch>,
+    ) {
+        let Some((letter, rest)) = rest.split_first() else {
+            // A single letter is read along every step, as a line search
+            // finds it in every direction
+            if path.len() == 1 {
+                found.extend(self.moves.iter().map(|&s| PathMatch {
+                    word,
+                    path: path.clone(),
+                    step: Some(s),
+                }));
+            } else {
+                found.push(PathMatch {
+                    word,
+                    path: path.clone(),
+                    step,
+                });
+            }
+            return;
+        };
+        let last = *path.last().unwrap();
+        for &next_step in &self.moves {
+            if step.is_some_and(|s| s != next_step) {
+                continue;
+            }
+            let Some(cell) = self.next(last, next_step, board.dim()) else {
+                continue;
+            };
+            if board[cell] != *letter || path.contains(&cell) {
+                continue;
+            }
+            path.push(cell);
+            let step = match self.walk {
+                Walk::Straight => Some(next_step),
+                Walk::Boggle => None,
+            };
+            self.extend(board, word, rest, path, step, found);
+            path.pop();
+        }
+    }
+}
+
+/// Finds every word of the list laid out as the search allows. A straight
+/// walk counts a path once for each step it is read along, as `find_words`
+/// does for directions, and a single letter is a match for every step in
+/// either walk. Otherwise a Boggle walk counts each path once.
+pub fn find_paths<T, W>(board: &Array2<T>, words: &[W], search: &PathSearch) -> Vec<PathMatch>
+where
+    T: PartialEq,
+    W: AsRef<[T]>,
+{
+    let mut matches = Vec::new();
+    for (index, word) in words.iter().enumerate() {
+        let Some((first, rest)) = word.as_ref().split_first() else {
+            continue;
+        };
+        for (start, _) in board.indexed_iter().filter(|(_, b)| *b == first) {
+            search.extend(board, index, rest, &mut vec![start], None, &mut matches);
+        }
+    }
+    matches.sort();
+    matches.dedup();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::board;
+    use crate::search::find_words;
+
+    fn search(moves: &[Step], wrap: bool, walk: Walk) -> PathSearch {
+        PathSearch {
+            moves: moves.to_vec(),
+            wrap,
+            walk,
+        }
+    }
+
+    #[test]
+    fn test_straight_matches_find_words() {
+        let board = board(&["XMASX", "SAMXM", "AMXSA", "MSAMS", "XMASX"]);
+        let words = ["XMAS", "AM", "SAMX", "X"];
+        let paths = find_paths(&board, &words, &search(&KING_MOVES, false, Walk::Straight));
+        let lines = find_words(&board, &words);
+        let mut expected = lines
+            .iter()
+            .map(|m| PathMatch {
+                word: m.word,
+                path: m.cells(words[m.word].len()).collect(),
+                step: Some(m.direction.step()),
+            })
+            .collect::<Vec<PathMatch>>();
+        expected.sort();
+        assert_eq!(paths, expected);
+    }
+
+    #[test]
+    fn test_wrap() {
+        let board = board(&["ASXM", "...."]);
+        let straight = search(&KING_MOVES, false, Walk::Straight);
+        assert!(find_paths(&board, &["XMAS"], &straight).is_empty());
+
+        let wrapped = search(&KING_MOVES, true, Walk::Straight);
+        assert_eq!(
+            find_paths(&board, &["XMAS"], &wrapped),
+            vec![PathMatch {
+                word: 0,
+                path: vec![(0, 2), (0, 3), (0, 0), (0, 1)],
+                step: Some((0, 1))
+            }]
+        );
+        // Going round the row a second time would reuse cells
+        assert!(find_paths(&board, &["XMASX"], &wrapped).is_empty());
+
+        // Matches that don't need to wrap count as they do without it
+        let board = crate::board::board(&["X..M", "M..X"]);
+        let words = ["X", "M"];
+        assert_eq!(find_paths(&board, &words, &wrapped).len(), 32);
+        assert_eq!(find_words(&board, &words).len(), 32);
+    }
+
+    #[test]
+    fn test_huge_steps() {
+        let board = board(&["X", "M", "A", "S"]);
+        // isize::MAX is one less than a multiple of four, so one step up
+        let wrapped = search(&[(isize::MAX, 0)], true, Walk::Straight);
+        assert_eq!(
+            find_paths(&board, &["SAMX"], &wrapped),
+            vec![PathMatch {
+                word: 0,
+                path: vec![(3, 0), (2, 0), (1, 0), (0, 0)],
+                step: Some((isize::MAX, 0))
+            }]
+        );
+        let straight = search(&[(isize::MAX, 0), (isize::MIN, 0)], false, Walk::Straight);
+        assert!(find_paths(&board, &["XM"], &straight).is_empty());
+    }
+
+    #[test]
+    fn test_knight_moves() {
+        let board = board(&["X..", "..M", "A..", "..S"]);
+        let knight = search(&KNIGHT_MOVES, false, Walk::Boggle);
+        assert_eq!(
+            find_paths(&board, &["XMAS"], &knight),
+            vec![PathMatch {
+                word: 0,
+                path: vec![(0, 0), (1, 2), (2, 0), (3, 2)],
+                step: None
+            }]
+        );
+        let straight = search(&KNIGHT_MOVES, false, Walk::Straight);
+        assert!(find_paths(&board, &["XMAS"], &straight).is_empty());
+    }
+
+    #[test]
+    fn test_boggle() {
+        let board = board(&["XM", "SA"]);
+        let boggle = search(&KING_MOVES, false, Walk::Boggle);
+        assert_eq!(find_paths(&board, &["XMAS", "XMAX"], &boggle).len(), 1);
+        // As many as a line search finds
+        assert_eq!(find_paths(&board, &["A"], &boggle).len(), 8);
+        assert_eq!(
+            find_paths(&board, &["XMAS"], &boggle)[0].path,
+            vec![(0, 0), (0, 1), (1, 1), (1, 0)]
+        );
+    }
+
+    #[test]
+    fn test_parse_moves() {
+        assert_eq!(parse_moves("knight"), Ok(KNIGHT_MOVES.to_vec()));
+        assert_eq!(parse_moves("0,1; -1, 0;0,1"), Ok(vec![(0, 1), (-1, 0)]));
+        assert!(parse_moves("0,0").is_err());
+        assert!(parse_moves("1").is_err());
+        assert!(parse_moves("").is_err());
+    }
+}
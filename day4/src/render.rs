@@ -0,0 +1,109 @@
+use clap::ValueEnum;
+use ndarray::Array2;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+
+use crate::board::Cell;
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Highlight {
+    /// Matched cells in bold yellow and the rest dimmed, using ANSI escapes
+    Color,
+    /// Matched cells as they are and the rest as `.`, like the puzzle's examples
+    Plain,
+}
+
+const MATCHED: &str = "\x1b[1;33m";
+const DIMMED: &str = "\x1b[2m";
+const RESET: &str = "\x1b[0m";
+
+/// A board sized mask with the given cells set.
+pub fn mark_cells<I>(shape: (usize, usize), cells: I) -> Array2<bool>
+where
+    I: IntoIterator<Item = (usize, usize)>,
+{
+    let mut mask = Array2::from_elem(shape, false);
+    for cell in cells {
+        mask[cell] = true;
+    }
+    mask
+}
+
+/// The named file, or stdout when there is none.
+pub fn open_output(path: Option<&str>) -> io::Result<Box<dyn Write>> {
+    Ok(match path {
+        Some(path) => Box::new(BufWriter::new(File::create(path)?)),
+        None => Box::new(io::stdout().lock()),
+    })
+}
+
+/// Writes the board one row per line, showing which cells the mask covers.
+pub fn write_board<T: Cell, W: Write>(
+    mut out: W,
+    board: &Array2<T>,
+    mask: &Array2<bool>,
+    highlight: Highlight,
+) -> io::Result<()> {
+    for (row, marks) in board.rows().into_iter().zip(mask.rows()) {
+        let mut line = Vec::with_capacity(row.len());
+        let mut current = None;
+        for (cell, &marked) in row.iter().zip(marks) {
+            match highlight {
+                Highlight::Plain if marked => cell.encode(&mut line),
+                Highlight::Plain => line.push(b'.'),
+                Highlight::Color => {
+                    // Only switch colour where a run of cells changes
+                    if current != Some(marked) {
+                        let code = if marked { MATCHED } else { DIMMED };
+                        line.extend_from_slice(RESET.as_bytes());
+                        line.extend_from_slice(code.as_bytes());
+                        current = Some(marked);
+                    }
+                    cell.encode(&mut line);
+                }
+            }
+        }
+        if highlight == Highlight::Color {
+            line.extend_from_slice(RESET.as_bytes());
+        }
+        line.push(b'\n');
+        out.write_all(&line)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::board;
+
+    fn render<T: Cell>(board: &Array2<T>, mask: &Array2<bool>, highlight: Highlight) -> String {
+        let mut out = Vec::new();
+        write_board(&mut out, board, mask, highlight).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_plain() {
+        let board = board(&["XMAS", "ABCD"]);
+        let mask = mark_cells(board.dim(), [(0, 1), (0, 2), (1, 3)]);
+        assert_eq!(render(&board, &mask, Highlight::Plain), ".MA.\n...D\n");
+    }
+
+    #[test]
+    fn test_plain_chars() {
+        let board = Array2::from_shape_vec((1, 3), vec!['é', 'ß', '中']).unwrap();
+        let mask = mark_cells(board.dim(), [(0, 0), (0, 2)]);
+        assert_eq!(render(&board, &mask, Highlight::Plain), "é.中\n");
+    }
+
+    #[test]
+    fn test_color() {
+        let board = board(&["XMAS"]);
+        let mask = mark_cells(board.dim(), [(0, 1), (0, 2)]);
+        assert_eq!(
+            render(&board, &mask, Highlight::Color),
+            "\x1b[0m\x1b[2mX\x1b[0m\x1b[1;33mMA\x1b[0m\x1b[2mS\x1b[0m\n"
+        );
+    }
+}
@@ -0,0 +1,271 @@
+use ndarray::Array2;
+use std::fmt;
+
+use crate::automaton::WordAutomaton;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction {
+    N,
+    S,
+    W,
+    E,
+    NW,
+    NE,
+    SW,
+    SE,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 8] = [
+        Direction::N,
+        Direction::S,
+        Direction::W,
+        Direction::E,
+        Direction::NW,
+        Direction::NE,
+        Direction::SW,
+        Direction::SE,
+    ];
+
+    /// The (row, column) change for one step in this direction.
+    pub fn step(&self) -> (isize, isize) {
+        match self {
+            Direction::N => (-1, 0),
+            Direction::S => (1, 0),
+            Direction::W => (0, -1),
+            Direction::E => (0, 1),
+            Direction::NW => (-1, -1),
+            Direction::NE => (-1, 1),
+            Direction::SW => (1, -1),
+            Direction::SE => (1, 1),
+        }
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// One occurrence of a word: its index in the word list, the (row, column)
+/// of its first letter and the direction it reads in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct WordMatch {
+    pub word: usize,
+    pub start: (usize, usize),
+    pub direction: Direction,
+}
+
+impl WordMatch {
+    /// The cells the word covers, given its length.
+    pub fn cells(&self, len: usize) -> impl Iterator<Item = (usize, usize)> {
+        let (dr, dc) = self.direction.step();
+        let (row, col) = self.start;
+        (0..len as isize).map(move |i| {
+            (
+                row.wrapping_add_signed(dr * i),
+                col.wrapping_add_signed(dc * i),
+            )
+        })
+    }
+}
+
+pub(crate) fn offset(
+    cell: (usize, usize),
+    step: (isize, isize),
+    shape: (usize, usize),
+) -> Option<(usize, usize)> {
+    let row = cell.0.checked_add_signed(step.0)?;
+    let col = cell.1.checked_add_signed(step.1)?;
+    (row < shape.0 && col < shape.1).then_some((row, col))
+}
+
+// Every maximal straight line through the board in one direction, as the
+// cells in reading order. Each cell is in exactly one line per direction.
+fn lines(shape: (usize, usize), direction: Direction) -> impl Iterator<Item = Vec<(usize, usize)>> {
+    let (dr, dc) = direction.step();
+    let back = (-dr, -dc);
+    (0..shape.0)
+        .flat_map(move |row| (0..shape.1).map(move |col| (row, col)))
+        .filter(move |&cell| offset(cell, back, shape).is_none())
+        .map(move |start| {
+            let mut line = vec![start];
+            while let Some(next) = offset(*line.last().unwrap(), (dr, dc), shape) {
+                line.push(next);
+            }
+            line
+        })
+}
+
+/// Finds every word of the list in all eight directions, running each line
+/// of the board through one automaton for the whole list.
+pub fn find_words<T, W>(board: &Array2<T>, words: &[W]) -> Vec<WordMatch>
+where
+    T: Clone + Ord,
+    W: AsRef<[T]>,
+{
+    let automaton = WordAutomaton::new(words);
+    let shape = board.dim();
+    let mut matches = Vec::new();
+    for direction in Direction::ALL {
+        for line in lines(shape, direction) {
+            let mut state = automaton.start();
+            for (i, &(row, col)) in line.iter().enumerate() {
+                state = automaton.step(state, &board[[row, col]]);
+                for &word in automaton.matches(state) {
+                    matches.push(WordMatch {
+                        word,
+                        start: line[i + 1 - automaton.word_len(word)],
+                        direction,
+                    });
+                }
+            }
+        }
+    }
+    matches.sort();
+    matches
+}
+
+/// How many times each word of the list was found.
+pub fn count_by_word(matches: &[WordMatch], nwords: usize) -> Vec<usize> {
+    let mut counts = vec![0; nwords];
+    for m in matches {
+        counts[m.word] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::board;
+
+    #[test]
+    fn test_find_words() {
+        let board = board(&["XMAS", "MM..", "A.A.", "SX.S"]);
+        let matches = find_words(&board, &["XMAS"]);
+        assert_eq!(matches.len(), 3);
+        assert_eq!(
+            matches,
+            vec![
+                WordMatch {
+                    word: 0,
+                    start: (0, 0),
+                    direction: Direction::S
+                },
+                WordMatch {
+                    word: 0,
+                    start: (0, 0),
+                    direction: Direction::E
+                },
+                WordMatch {
+                    word: 0,
+                    start: (0, 0),
+                    direction: Direction::SE
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_words_in_chars() {
+        let board = Array2::from_shape_vec((2, 3), "ÉTÉxÉy".chars().collect()).unwrap();
+        let words = [vec!['É', 'T', 'É'], vec!['É', 'É']];
+        let matches = find_words(&board, &words);
+        assert_eq!(count_by_word(&matches, words.len()), vec![2, 4]);
+    }
+
+    #[test]
+    fn test_match_cells() {
+        let m = WordMatch {
+            word: 0,
+            start: (3, 1),
+            direction: Direction::NE,
+        };
+        assert_eq!(
+            m.cells(3).collect::<Vec<(usize, usize)>>(),
+            vec![(3, 1), (2, 2), (1, 3)]
+        );
+    }
+
+    // The original single word search, one start cell and direction at a time
+    fn brute_force(board: &Array2<u8>, word: &[u8]) -> Vec<WordMatch> {
+        let mut matches = Vec::new();
+        for ((row, col), _) in board.indexed_iter() {
+            for direction in Direction::ALL {
+                let mut cell = Some((row, col));
+                let found = word.iter().all(|&b| match cell {
+                    Some(c) if board[c] == b => {
+                        cell = offset(c, direction.step(), board.dim());
+                        true
+                    }
+                    _ => false,
+                });
+                if found {
+                    matches.push(WordMatch {
+                        word: 0,
+                        start: (row, col),
+                        direction,
+                    });
+                }
+            }
+        }
+        matches
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let board = board(&[
+            "MMMSXXMASM",
+            "MSAMXMSMSA",
+            "AMXSXMAAMM",
+            "MSAMASMSMX",
+            "XMASAMXAMM",
+            "XXAMMXXAMA",
+            "SMSMSASXSS",
+            "SAXAMASAAA",
+            "MAMMMXMMMM",
+            "MXMXAXMASX",
+        ]);
+        assert_eq!(find_words(&board, &["XMAS"]).len(), 18);
+    }
+
+    #[test]
+    fn test_many_words_one_pass() {
+        let board = board(&["CATS", "ODOG", "WOGO", "SAAS"]);
+        let words = ["CAT", "DOG", "COW", "TAC", "GO", "O", "AA", "SAAS", "CODS"];
+        let matches = find_words(&board, &words);
+        for (index, word) in words.iter().enumerate() {
+            let mut expected = brute_force(&board, word.as_bytes());
+            expected.iter_mut().for_each(|m| m.word = index);
+            expected.sort();
+            let found = matches
+                .iter()
+                .filter(|m| m.word == index)
+                .copied()
+                .collect::<Vec<WordMatch>>();
+            assert_eq!(found, expected, "{}", word);
+        }
+        assert!(matches.contains(&WordMatch {
+            word: 1,
+            start: (1, 1),
+            direction: Direction::E
+        }));
+        // A single letter reads the same in all eight directions
+        assert_eq!(count_by_word(&matches, words.len())[5], 4 * 8);
+    }
+
+    #[test]
+    fn test_lines_cover_board_once() {
+        for direction in Direction::ALL {
+            let mut cells = lines((3, 5), direction)
+                .flatten()
+                .collect::<Vec<(usize, usize)>>();
+            cells.sort();
+            cells.dedup();
+            assert_eq!(cells.len(), 15, "{}", direction);
+            assert_eq!(lines((3, 5), direction).flatten().count(), 15);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use ndarray::{s, Array2, Zip};
+use std::fmt;
+use std::fs;
+use std::io;
+
+/// How a stencil was turned before matching: mirrored left to right or not,
+/// then rotated clockwise by a number of quarter turns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Orientation {
+    pub reflected: bool,
+    pub quarter_turns: u8,
+}
+
+impl Orientation {
+    pub const ALL: [Orientation; 8] = [
+        Orientation::new(false, 0),
+        Orientation::new(false, 1),
+        Orientation::new(false, 2),
+        Orientation::new(false, 3),
+        Orientation::new(true, 0),
+        Orientation::new(true, 1),
+        Orientation::new(true, 2),
+        Orientation::new(true, 3),
+    ];
+
+    const fn new(reflected: bool, quarter_turns: u8) -> Orientation {
+        Orientation {
+            reflected,
+            quarter_turns,
+        }
+    }
+
+    fn apply(&self, pattern: &Array2<u8>) -> Array2<u8> {
+        let mut turned = if self.reflected {
+            pattern.slice(s![.., ..;-1]).to_owned()
+        } else {
+            pattern.clone()
+        };
+        for _ in 0..self.quarter_turns {
+            // Clockwise: the first column, read bottom up, becomes the first row
+            turned = turned.t().slice(s![.., ..;-1]).to_owned();
+        }
+        turned
+    }
+}
+
+impl fmt::Display for Orientation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.reflected {
+            write!(f, "reflected, ")?;
+        }
+        write!(f, "rotated {}", self.quarter_turns as usize * 90)
+    }
+}
+
+/// A rectangle of letters to look for, where the wildcard matches anything.
+/// Every distinct rotation and reflection is matched; symmetric stencils have
+/// fewer than eight.
+#[derive(Debug, Clone)]
+pub struct Stencil {
+    wildcard: u8,
+    variants: Vec<(Orientation, Array2<u8>)>,
+}
+
+impl Stencil {
+    pub fn new(pattern: Array2<u8>, wildcard: u8) -> Stencil {
+        let mut variants: Vec<(Orientation, Array2<u8>)> = Vec::new();
+        for orientation in Orientation::ALL {
+            let turned = orientation.apply(&pattern);
+            if variants.iter().all(|(_, v)| *v != turned) {
+                variants.push((orientation, turned));
+            }
+        }
+        Stencil { wildcard, variants }
+    }
+
+    /// The distinct orientations, starting with the stencil as written.
+    pub fn variants(&self) -> &[(Orientation, Array2<u8>)] {
+        &self.variants
+    }
+}
+
+/// The X-MAS from the puzzle. Its wildcard is `.` whatever wildcard stencil
+/// files use, so it can't turn any of its letters into wildcards.
+pub fn x_mas() -> Stencil {
+    let pattern = Array2::from_shape_vec((3, 3), b"M.M.A.S.S".to_vec()).unwrap();
+    Stencil::new(pattern, b'.')
+}
+
+/// One placement of a stencil: its index in the list, the orientation that
+/// matched and the (row, column) of its top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct StencilMatch {
+    pub stencil: usize,
+    pub orientation: Orientation,
+    pub start: (usize, usize),
+}
+
+impl StencilMatch {
+    /// The board cells this match checked, leaving out the wildcards.
+    pub fn cells<'a>(&self, stencils: &'a [Stencil]) -> impl Iterator<Item = (usize, usize)> + 'a {
+        let stencil = &stencils[self.stencil];
+        let (_, pattern) = stencil
+            .variants
+            .iter()
+            .find(|(o, _)| *o == self.orientation)
+            .expect("Match for an orientation the stencil does not have");
+        let (row, col) = self.start;
+        pattern
+            .indexed_iter()
+            .filter(|(_, &p)| p != stencil.wildcard)
+            .map(move |((r, c), _)| (row + r, col + c))
+    }
+}
+
+/// Reads stencils separated by blank lines. Rows of a stencil must all be the
+/// same length. The wildcard can't be a letter or digit, which a stencil might
+/// need to match, but it can be a space.
+pub fn parse_stencils(text: &str, wildcard: u8) -> io::Result<Vec<Stencil>> {
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+    if wildcard.is_ascii_alphanumeric() {
+        return Err(invalid(format!(
+            "The wildcard '{}' could not be told apart from a letter",
+            wildcard as char
+        )));
+    }
+    let mut stencils = Vec::new();
+    let mut rows: Vec<&[u8]> = Vec::new();
+    for (line_no, line) in text.lines().chain([""]).enumerate() {
+        // Trailing whitespace is dropped unless it is the wildcard
+        let line = line
+            .trim_end_matches(|c: char| c.is_whitespace() && c != wildcard as char)
+            .as_bytes();
+        if !line.is_empty() {
+            if let Some(first) = rows.first() {
+                if first.len() != line.len() {
+                    return Err(invalid(format!(
+                        "Invalid number of columns ({}) on line {}, expected {}",
+                        line.len(),
+                        line_no + 1,
+                        first.len()
+                    )));
+                }
+            }
+            rows.push(line);
+            continue;
+        }
+        if rows.is_empty() {
+            continue;
+        }
+        let pattern = Array2::from_shape_vec(
+            (rows.len(), rows[0].len()),
+            rows.drain(..).flatten().copied().collect(),
+        )
+        .unwrap();
+        stencils.push(Stencil::new(pattern, wildcard));
+    }
+    if stencils.is_empty() {
+        return Err(invalid("No stencils found".to_string()));
+    }
+    Ok(stencils)
+}
+
+pub fn read_stencils(path: &str, wildcard: u8) -> io::Result<Vec<Stencil>> {
+    parse_stencils(&fs::read_to_string(path)?, wildcard)
+}
+
+/// Finds every placement of every orientation of the stencils. Two
+/// orientations that both fit at the same place are separate matches.
+pub fn find_stencils(board: &Array2<u8>, stencils: &[Stencil]) -> Vec<StencilMatch> {
+    let (nrows, ncols) = board.dim();
+    let mut matches = Vec::new();
+    for (index, stencil) in stencils.iter().enumerate() {
+        for (orientation, pattern) in &stencil.variants {
+            let (height, width) = pattern.dim();
+            if height > nrows || width > ncols {
+                continue;
+            }
+            for row in 0..=nrows - height {
+                for col in 0..=ncols - width {
+                    let window = board.slice(s![row..row + height, col..col + width]);
+                    let found = Zip::from(pattern)
+                        .and(&window)
+                        .all(|&p, &b| p == stencil.wildcard || p == b);
+                    if found {
+                        matches.push(StencilMatch {
+                            stencil: index,
+                            orientation: *orientation,
+                            start: (row, col),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    matches.sort();
+    matches
+}
+
+/// How many times each stencil of the list was found.
+pub fn count_by_stencil(matches: &[StencilMatch], nstencils: usize) -> Vec<usize> {
+    let mut counts = vec![0; nstencils];
+    for m in matches {
+        counts[m.stencil] += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::board::board;
+
+    #[test]
+    fn test_find_x_mas() {
+        let board = board(&[".M.S", "..A.", ".M.S", "SX.S"]);
+        let stencils = parse_stencils("M.M\n.A.\nS.S\n", b'.').unwrap();
+        let matches = find_stencils(&board, &stencils);
+        assert_eq!(
+            matches,
+            vec![StencilMatch {
+                stencil: 0,
+                orientation: Orientation::new(false, 3),
+                start: (0, 1)
+            }]
+        );
+    }
+
+    #[test]
+    fn test_match_cells() {
+        let board = board(&[".M.S", "..A.", ".M.S", "SX.S"]);
+        let stencils = parse_stencils("M.M\n.A.\nS.S\n", b'.').unwrap();
+        let matches = find_stencils(&board, &stencils);
+        assert_eq!(
+            matches[0].cells(&stencils).collect::<Vec<(usize, usize)>>(),
+            vec![(0, 1), (0, 3), (1, 2), (2, 1), (2, 3)]
+        );
+    }
+
+    #[test]
+    fn test_rotations() {
+        let stencils = parse_stencils("AB\nCD\n", b'.').unwrap();
+        let variants = stencils[0].variants();
+        assert_eq!(variants.len(), 8);
+        assert_eq!(variants[1].1, board(&["CA", "DB"]));
+        assert_eq!(variants[4].1, board(&["BA", "DC"]));
+        assert_eq!(variants[5].1, board(&["DB", "CA"]));
+
+        let stencils = parse_stencils("ABC\n", b'.').unwrap();
+        assert_eq!(stencils[0].variants()[1].1, board(&["A", "B", "C"]));
+    }
+
+    #[test]
+    fn test_symmetric_stencils_deduplicated() {
+        let stencils = parse_stencils("M.M\n.A.\nS.S\n\nA.A\n.A.\nA.A\n\nXY\nYX", b'.').unwrap();
+        let counts = stencils
+            .iter()
+            .map(|s| s.variants().len())
+            .collect::<Vec<usize>>();
+        assert_eq!(counts, vec![4, 1, 2]);
+    }
+
+    #[test]
+    fn test_puzzle_example() {
+        let board = board(&[
+            "MMMSXXMASM",
+            "MSAMXMSMSA",
+            "AMXSXMAAMM",
+            "MSAMASMSMX",
+            "XMASAMXAMM",
+            "XXAMMXXAMA",
+            "SMSMSASXSS",
+            "SAXAMASAAA",
+            "MAMMMXMMMM",
+            "MXMXAXMASX",
+        ]);
+        let stencils = parse_stencils("M.M\n.A.\nS.S\n\nXMAS\n", b'.').unwrap();
+        let matches = find_stencils(&board, &stencils);
+        // Stencils only turn by quarter turns, so XMAS is not found diagonally
+        assert_eq!(count_by_stencil(&matches, stencils.len()), vec![9, 8]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_stencils("\n\n", b'.').is_err());
+        assert!(parse_stencils("AB\nC\n", b'.').is_err());
+        assert!(parse_stencils("M.M\n.A.\nS.S\n", b'M').is_err());
+    }
+
+    #[test]
+    fn test_x_mas_ignores_other_wildcards() {
+        // With M as the wildcard this would match, as if M were anything
+        let matches = find_stencils(&board(&["XQX", "QAQ", "SQS"]), &[x_mas()]);
+        assert!(matches.is_empty());
+        let matches = find_stencils(&board(&["MQM", "QAQ", "SQS"]), &[x_mas()]);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(
+            x_mas().variants(),
+            parse_stencils("M.M\n.A.\nS.S\n", b'.').unwrap()[0].variants()
+        );
+    }
+
+    #[test]
+    fn test_space_wildcard() {
+        // The trailing spaces are wildcards, not padding to trim
+        let stencils = parse_stencils("M M\n A \nS S\n\nXM \n", b' ').unwrap();
+        assert_eq!(stencils[1].variants()[0].1, board(&["XM "]));
+        let board = board(&[".M.S", "..A.", ".M.S", "SX.S"]);
+        let matches = find_stencils(&board, &stencils);
+        assert_eq!(count_by_stencil(&matches, stencils.len()), vec![1, 1]);
+    }
+}
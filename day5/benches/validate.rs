@@ -0,0 +1,72 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::collections::HashMap;
+
+use day5::order::{is_valid, is_valid_quadratic};
+
+const PAGES: u32 = 10_000;
+// Each page must come before the next RULES_PER_PAGE pages
+const RULES_PER_PAGE: u32 = 100;
+
+fn rules() -> HashMap<u32, Vec<u32>> {
+    (0..PAGES)
+        .map(|page| {
+            (
+                page,
+                (page + 1..PAGES.min(page + 1 + RULES_PER_PAGE)).collect(),
+            )
+        })
+        .collect()
+}
+
+// Valid updates are the worst case, as neither check can stop early. Pages
+// are spread with a fixed linear congruential generator so runs compare.
+fn updates(len: usize, count: usize) -> Vec<Vec<u32>> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..count)
+        .map(|_| {
+            let mut update = (0..len)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    ((state >> 33) % PAGES as u64) as u32
+                })
+                .collect::<Vec<u32>>();
+            update.sort();
+            update.dedup();
+            update
+        })
+        .collect()
+}
+
+fn bench_validation(c: &mut Criterion) {
+    let rules = rules();
+    let mut group = c.benchmark_group("validate updates");
+    for len in [5, 10, 25, 100, 400, 1600] {
+        let updates = updates(len, 20);
+        group.bench_with_input(BenchmarkId::new("indexed", len), &updates, |b, updates| {
+            b.iter(|| {
+                updates
+                    .iter()
+                    .filter(|update| is_valid(&rules, black_box(update)))
+                    .count()
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("quadratic", len),
+            &updates,
+            |b, updates| {
+                b.iter(|| {
+                    updates
+                        .iter()
+                        .filter(|update| is_valid_quadratic(&rules, black_box(update)))
+                        .count()
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_validation);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+use clap::Parser;
+
+use day5::order::find_valid_updates;
+use day5::parse_input;
+
+#[derive(Parser)]
+struct Args {
+    file: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    let (rules, updates) = parse_input(args.file).unwrap();
+    let updates_to_print = find_valid_updates(&rules, &updates);
+    println!(
+        "valid updates: {}/{}",
+        updates_to_print.len(),
+        updates.len()
+    );
+    let sum = updates_to_print
+        .iter()
+        .map(|update| update[update.len() / 2])
+        .sum::<u32>();
+    println!("sum: {}", sum);
+}
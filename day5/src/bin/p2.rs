@@ -0,0 +1,32 @@
+use clap::Parser;
+
+use day5::order::{find_invalid_updates, fix_updates};
+use day5::parse_input;
+
+#[derive(Parser)]
+struct Args {
+    file: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    let (rules, updates) = parse_input(args.file).unwrap();
+    let invalid_updates = find_invalid_updates(&rules, &updates);
+    let corrected_updates = match fix_updates(&rules, &invalid_updates) {
+        Ok(updates) => updates,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "invalid updates: {}/{}",
+        invalid_updates.len(),
+        updates.len()
+    );
+    let sum = corrected_updates
+        .iter()
+        .map(|update| update[update.len() / 2])
+        .sum::<u32>();
+    println!("sum: {}", sum);
+}
@@ -0,0 +1,67 @@
+use clap::Parser;
+use std::process;
+
+use day5::order::format_cycle;
+use day5::parse_input;
+use day5::validate::validate_rules;
+
+/// Checks the page ordering rules for contradictions, duplicates and rules
+/// that no update can use. Exits with an error if any rules contradict.
+#[derive(Parser)]
+struct Args {
+    file: String,
+}
+
+fn main() {
+    let args = Args::parse();
+    let (rules, updates) = parse_input(args.file).unwrap();
+    let validation = validate_rules(&rules, &updates);
+
+    for contradiction in &validation.contradictions {
+        let pages = contradiction
+            .pages
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<String>>();
+        println!(
+            "contradiction between pages {}: shortest cycle {}",
+            pages.join(","),
+            format_cycle(&contradiction.cycle)
+        );
+    }
+    for duplicate in &validation.duplicates {
+        println!(
+            "duplicate rule {}|{} given {} times",
+            duplicate.rule.0, duplicate.rule.1, duplicate.count
+        );
+    }
+    for unused in &validation.unused {
+        let missing = unused
+            .missing
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<String>>();
+        let (noun, verb) = if missing.len() == 1 {
+            ("page", "is")
+        } else {
+            ("pages", "are")
+        };
+        println!(
+            "unused rule {}|{}: {} {} {} in no update",
+            unused.rule.0,
+            unused.rule.1,
+            noun,
+            missing.join(" and "),
+            verb
+        );
+    }
+    println!(
+        "contradictions: {}, duplicate rules: {}, unused rules: {}",
+        validation.contradictions.len(),
+        validation.duplicates.len(),
+        validation.unused.len()
+    );
+    if !validation.contradictions.is_empty() {
+        process::exit(1);
+    }
+}
@@ -0,0 +1,87 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+pub mod order;
+pub mod validate;
+
+#[derive(Debug)]
+pub struct InputParseError {
+    message: String,
+}
+
+impl std::fmt::Display for InputParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for InputParseError {}
+
+impl From<String> for InputParseError {
+    fn from(message: String) -> Self {
+        InputParseError { message }
+    }
+}
+
+/// The rules, as the pages that must come after each page, and the updates.
+pub type Input = (HashMap<u32, Vec<u32>>, Vec<Vec<u32>>);
+
+pub fn parse_input(file: String) -> Result<Input, Box<dyn Error>> {
+    let file = File::open(file)?;
+    let reader = BufReader::new(file);
+    let mut rules: HashMap<u32, Vec<u32>> = HashMap::new();
+    let mut updates = Vec::new();
+
+    let mut line_no = 0;
+    let mut lines_iter = reader.lines().enumerate();
+    // Parse the rules
+    for (i, line_raw) in lines_iter.by_ref() {
+        line_no = i + 1;
+        let line =
+            line_raw.map_err(|e| format!("I/O Error reading rules, line {}: {}", line_no, e))?;
+
+        if line.is_empty() {
+            break;
+        }
+        let (p1s, p2s) = match line.split_once("|") {
+            Some((p1, p2)) => (p1, p2),
+            None => {
+                return Err(Box::new(InputParseError {
+                    message: format!("Line {}: Expected '|' separator", line_no),
+                }))
+            }
+        };
+        let p1 = p1s
+            .parse::<u32>()
+            .map_err(|e| format!("Line {}: Expected number, got {}: {}", line_no, p1s, e))?;
+        let p2 = p2s
+            .parse::<u32>()
+            .map_err(|e| format!("Line {}: Expected number, got {}: {}", line_no, p2s, e))?;
+
+        let rule = rules.entry(p1).or_default();
+        rule.push(p2);
+    }
+
+    let rules_last_line = line_no;
+    // Parse the updates
+    for (i, line_raw) in lines_iter {
+        let line_no = rules_last_line + i + 1;
+        let line =
+            line_raw.map_err(|e| format!("I/O Error reading updates, line {}: {}", line_no, e))?;
+
+        let pages = line
+            .split(',')
+            .map(|page_str| {
+                page_str.parse::<u32>().map_err(|e| {
+                    format!("Line {}: Expected number, got {}: {}", line_no, page_str, e)
+                })
+            })
+            .collect::<Result<Vec<u32>, String>>()?;
+
+        updates.push(pages);
+    }
+
+    Ok((rules, updates))
+}
@@ -0,0 +1,330 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use std::hash::{BuildHasherDefault, Hasher};
+
+// Page numbers come from the puzzle input, not an attacker, so one multiply
+// hashes them well enough. The default SipHash made indexing a short update
+// slower than scanning it.
+#[derive(Default)]
+struct PageHasher(u64);
+
+impl Hasher for PageHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.write_u32(b as u32);
+        }
+    }
+
+    fn write_u32(&mut self, n: u32) {
+        self.0 = (self.0 ^ n as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+    }
+
+    // The table picks buckets from the low bits, which the multiply leaves poorly mixed
+    fn finish(&self) -> u64 {
+        self.0 ^ (self.0 >> 32)
+    }
+}
+
+type PagePositions = HashMap<u32, (usize, usize), BuildHasherDefault<PageHasher>>;
+
+/// Whether every rule between the update's pages holds. Indexes where each
+/// page first and last appears, then checks each rule of each distinct page
+/// once, so this is linear in the update and its pages' rules.
+pub fn is_valid(rules: &HashMap<u32, Vec<u32>>, update: &[u32]) -> bool {
+    let mut positions = PagePositions::with_capacity_and_hasher(update.len(), Default::default());
+    for (pos, &page) in update.iter().enumerate() {
+        positions.entry(page).or_insert((pos, pos)).1 = pos;
+    }
+    // A rule X|Y is broken when any Y comes before any X
+    positions.iter().all(|(page, &(_, last))| {
+        rules.get(page).into_iter().flatten().all(|after| {
+            positions
+                .get(after)
+                .is_none_or(|&(after_first, _)| after_first >= last)
+        })
+    })
+}
+
+/// The original check, kept to compare against `is_valid`.
+pub fn is_valid_quadratic(rules: &HashMap<u32, Vec<u32>>, update: &[u32]) -> bool {
+    // This is the dumb O(n^2*m) solution where n is the number of pages in the udpate
+    // and m is the maximum number of rules for any page
+    for (pos, page) in update.iter().enumerate() {
+        let Some(rule) = rules.get(page) else {
+            continue;
+        };
+        if rule
+            .iter()
+            .any(|rule_page| update[..pos].contains(rule_page))
+        {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn find_valid_updates<'a>(
+    rules: &HashMap<u32, Vec<u32>>,
+    updates: &'a [Vec<u32>],
+) -> Vec<&'a Vec<u32>> {
+    updates
+        .iter()
+        .filter(|update| is_valid(rules, update))
+        .collect()
+}
+
+pub fn find_invalid_updates<'a>(
+    rules: &HashMap<u32, Vec<u32>>,
+    updates: &'a [Vec<u32>],
+) -> Vec<&'a Vec<u32>> {
+    updates
+        .iter()
+        .filter(|update| !is_valid(rules, update))
+        .collect()
+}
+
+/// An update whose pages cannot be ordered because the rules between them
+/// go round in a circle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CycleError {
+    pub update: Vec<u32>,
+    /// Pages in rule order, each one required before the next and the last
+    /// before the first
+    pub cycle: Vec<u32>,
+}
+
+/// The rules that make up a cycle, e.g. `1|2, 2|3, 3|1`.
+pub fn format_cycle(cycle: &[u32]) -> String {
+    cycle
+        .iter()
+        .zip(cycle.iter().cycle().skip(1))
+        .map(|(before, after)| format!("{}|{}", before, after))
+        .collect::<Vec<String>>()
+        .join(", ")
+}
+
+impl std::fmt::Display for CycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let update = self
+            .update
+            .iter()
+            .map(|page| page.to_string())
+            .collect::<Vec<String>>();
+        write!(
+            f,
+            "Cannot order update {}: rules form a cycle {}",
+            update.join(","),
+            format_cycle(&self.cycle)
+        )
+    }
+}
+
+impl std::error::Error for CycleError {}
+
+// Follows rules backwards from a page left over by the sort until a page
+// repeats. Every left over page has a rule from another left over page, so
+// this always finds a cycle.
+fn find_cycle(update: &[u32], preds: &[Vec<usize>], remaining: &[bool]) -> Vec<u32> {
+    let mut pos = remaining.iter().position(|&r| r).unwrap();
+    let mut walk = Vec::new();
+    while !walk.contains(&pos) {
+        walk.push(pos);
+        pos = *preds[pos].iter().find(|&&p| remaining[p]).unwrap();
+    }
+    let start = walk.iter().position(|&p| p == pos).unwrap();
+    walk[start..].iter().rev().map(|&p| update[p]).collect()
+}
+
+/// Orders the pages so every rule between them holds, using Kahn's algorithm
+/// on the rules between the update's pages. When several pages could come
+/// next, the one earliest in the update goes first, so pages the rules don't
+/// constrain keep their order.
+pub fn fix_update(rules: &HashMap<u32, Vec<u32>>, update: &[u32]) -> Result<Vec<u32>, CycleError> {
+    let mut positions: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (pos, page) in update.iter().enumerate() {
+        positions.entry(*page).or_default().push(pos);
+    }
+
+    let mut succs = vec![Vec::new(); update.len()];
+    let mut preds = vec![Vec::new(); update.len()];
+    for (pos, page) in update.iter().enumerate() {
+        for after in rules.get(page).into_iter().flatten() {
+            for &after_pos in positions.get(after).into_iter().flatten() {
+                if after_pos != pos {
+                    succs[pos].push(after_pos);
+                    preds[after_pos].push(pos);
+                }
+            }
+        }
+    }
+
+    let mut in_degree = preds.iter().map(|p| p.len()).collect::<Vec<usize>>();
+    let mut ready = (0..update.len())
+        .filter(|&pos| in_degree[pos] == 0)
+        .map(Reverse)
+        .collect::<BinaryHeap<Reverse<usize>>>();
+    let mut remaining = vec![true; update.len()];
+    let mut fixed_update = Vec::with_capacity(update.len());
+    while let Some(Reverse(pos)) = ready.pop() {
+        fixed_update.push(update[pos]);
+        remaining[pos] = false;
+        for &next in &succs[pos] {
+            in_degree[next] -= 1;
+            if in_degree[next] == 0 {
+                ready.push(Reverse(next));
+            }
+        }
+    }
+
+    if fixed_update.len() < update.len() {
+        return Err(CycleError {
+            update: update.to_vec(),
+            cycle: find_cycle(update, &preds, &remaining),
+        });
+    }
+    Ok(fixed_update)
+}
+
+pub fn fix_updates(
+    rules: &HashMap<u32, Vec<u32>>,
+    invalid_updates: &[&Vec<u32>],
+) -> Result<Vec<Vec<u32>>, CycleError> {
+    invalid_updates
+        .iter()
+        .map(|update| fix_update(rules, update))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    proptest! {
+        // Few pages, so updates repeat pages and rules often apply
+        #[test]
+        fn test_is_valid_matches_quadratic(
+            pairs in prop::collection::vec((0u32..8, 0u32..8), 0..20),
+            update in prop::collection::vec(0u32..8, 0..10),
+        ) {
+            let mut rules: HashMap<u32, Vec<u32>> = HashMap::new();
+            for (before, after) in pairs {
+                rules.entry(before).or_default().push(after);
+            }
+            prop_assert_eq!(is_valid(&rules, &update), is_valid_quadratic(&rules, &update));
+        }
+    }
+
+    #[test]
+    fn test_find_valid_updates_no_rules() {
+        let rules = HashMap::new();
+        let updates = vec![vec![1, 2, 3], vec![2, 1, 3]];
+        let valid_updates = find_valid_updates(&rules, &updates);
+        assert_eq!(valid_updates.len(), 2);
+    }
+
+    #[test]
+    fn test_find_valid_updates_1_rule() {
+        let mut rules = HashMap::new();
+        rules.insert(1, vec![2]);
+        let updates = vec![vec![1, 2, 3], vec![2, 1, 3]];
+        let valid_updates = find_valid_updates(&rules, &updates);
+        assert_eq!(valid_updates, vec![&vec![1, 2, 3]]);
+    }
+
+    #[test]
+    fn test_find_invalid_updates_no_rules() {
+        let rules = HashMap::new();
+        let updates = vec![vec![1, 2, 3], vec![2, 1, 3]];
+        let valid_updates = find_invalid_updates(&rules, &updates);
+        assert_eq!(valid_updates.len(), 0);
+    }
+
+    #[test]
+    fn test_find_invalid_updates_1_rule() {
+        let mut rules = HashMap::new();
+        rules.insert(1, vec![2]);
+        let updates = vec![vec![1, 2, 3], vec![2, 1, 3]];
+        let valid_updates = find_invalid_updates(&rules, &updates);
+        assert_eq!(valid_updates.len(), 1);
+    }
+
+    #[test]
+    fn test_fix_updates() {
+        let mut rules = HashMap::new();
+        rules.insert(1, vec![2]);
+        let updates = [vec![2, 1, 3]];
+        let invalid_updates = updates.iter().collect::<Vec<&Vec<u32>>>();
+        let fixed_updates = fix_updates(&rules, &invalid_updates).unwrap();
+        assert_eq!(fixed_updates, vec![vec![1, 2, 3]]);
+    }
+
+    fn example_rules() -> HashMap<u32, Vec<u32>> {
+        let mut rules: HashMap<u32, Vec<u32>> = HashMap::new();
+        for (before, after) in [
+            (47, 53),
+            (97, 13),
+            (97, 61),
+            (97, 47),
+            (75, 29),
+            (61, 13),
+            (75, 53),
+            (29, 13),
+            (97, 29),
+            (53, 29),
+            (61, 53),
+            (97, 53),
+            (61, 29),
+            (47, 13),
+            (75, 47),
+            (97, 75),
+            (47, 61),
+            (75, 61),
+            (47, 29),
+            (75, 13),
+            (53, 13),
+        ] {
+            rules.entry(before).or_default().push(after);
+        }
+        rules
+    }
+
+    #[test]
+    fn test_fix_update_example() {
+        let rules = example_rules();
+        assert_eq!(
+            fix_update(&rules, &[75, 97, 47, 61, 53]),
+            Ok(vec![97, 75, 47, 61, 53])
+        );
+        assert_eq!(fix_update(&rules, &[61, 13, 29]), Ok(vec![61, 29, 13]));
+        assert_eq!(
+            fix_update(&rules, &[97, 13, 75, 29, 47]),
+            Ok(vec![97, 75, 47, 29, 13])
+        );
+    }
+
+    #[test]
+    fn test_fix_update_keeps_unconstrained_order() {
+        let mut rules = HashMap::new();
+        rules.insert(5, vec![1]);
+        assert_eq!(
+            fix_update(&rules, &[9, 1, 8, 5, 7]),
+            Ok(vec![9, 8, 5, 1, 7])
+        );
+    }
+
+    #[test]
+    fn test_fix_update_cycle() {
+        let mut rules = HashMap::new();
+        rules.insert(1, vec![2]);
+        rules.insert(2, vec![3]);
+        rules.insert(3, vec![1]);
+        rules.insert(4, vec![1]);
+        let err = fix_update(&rules, &[4, 3, 2, 1]).unwrap_err();
+        assert_eq!(err.cycle, vec![1, 2, 3]);
+        assert_eq!(
+            err.to_string(),
+            "Cannot order update 4,3,2,1: rules form a cycle 1|2, 2|3, 3|1"
+        );
+    }
+}
@@ -0,0 +1,322 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Pages whose rules contradict each other: a strongly connected component
+/// of the rule graph, and one of the shortest cycles through it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contradiction {
+    /// Every page in the component, in ascending order
+    pub pages: Vec<u32>,
+    /// Each page must come before the next, and the last before the first
+    pub cycle: Vec<u32>,
+}
+
+/// A rule given more than once.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Duplicate {
+    pub rule: (u32, u32),
+    pub count: usize,
+}
+
+/// A rule that can never apply, because some of its pages are in no update.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnusedRule {
+    pub rule: (u32, u32),
+    pub missing: Vec<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Validation {
+    pub contradictions: Vec<Contradiction>,
+    pub duplicates: Vec<Duplicate>,
+    pub unused: Vec<UnusedRule>,
+}
+
+// Tarjan's algorithm with an explicit stack, so long chains of rules can't
+// overflow the call stack. Returns the components in no particular order.
+fn strongly_connected(adj: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    let mut index = vec![UNSEEN; adj.len()];
+    let mut low = vec![0; adj.len()];
+    let mut on_stack = vec![false; adj.len()];
+    let mut stack = Vec::new();
+    let mut next_index = 0;
+    let mut components = Vec::new();
+
+    for root in 0..adj.len() {
+        if index[root] != UNSEEN {
+            continue;
+        }
+        // Each frame is a node and how many of its edges have been followed
+        let mut calls = vec![(root, 0)];
+        index[root] = next_index;
+        low[root] = next_index;
+        next_index += 1;
+        stack.push(root);
+        on_stack[root] = true;
+
+        while let Some((v, edge)) = calls.last_mut() {
+            let v = *v;
+            if let Some(&w) = adj[v].get(*edge) {
+                *edge += 1;
+                if index[w] == UNSEEN {
+                    index[w] = next_index;
+                    low[w] = next_index;
+                    next_index += 1;
+                    stack.push(w);
+                    on_stack[w] = true;
+                    calls.push((w, 0));
+                } else if on_stack[w] {
+                    low[v] = low[v].min(index[w]);
+                }
+                continue;
+            }
+
+            calls.pop();
+            if let Some(&(parent, _)) = calls.last() {
+                low[parent] = low[parent].min(low[v]);
+            }
+            if low[v] == index[v] {
+                let mut component = Vec::new();
+                loop {
+                    let w = stack.pop().unwrap();
+                    on_stack[w] = false;
+                    component.push(w);
+                    if w == v {
+                        break;
+                    }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+const UNSEEN: usize = usize::MAX;
+
+// Breadth first search from each node for the shortest way back to it,
+// staying inside the component. Ties go to the lowest numbered start.
+// `parent` is scratch space, all UNSEEN between calls, so it is allocated
+// once rather than for every component.
+fn shortest_cycle(
+    adj: &[Vec<usize>],
+    component: &[usize],
+    component_of: &[usize],
+    parent: &mut [usize],
+) -> Option<Vec<usize>> {
+    let id = component_of[component[0]];
+    let mut best: Option<Vec<usize>> = None;
+    for &start in component {
+        parent[start] = start;
+        let mut queue = VecDeque::from([start]);
+        let mut seen = vec![start];
+        let mut closing = None;
+        while let Some(v) = queue.pop_front() {
+            if adj[v].contains(&start) {
+                closing = Some(v);
+                break;
+            }
+            for &w in &adj[v] {
+                if component_of[w] == id && parent[w] == UNSEEN {
+                    parent[w] = v;
+                    seen.push(w);
+                    queue.push_back(w);
+                }
+            }
+        }
+        if let Some(mut v) = closing {
+            let mut cycle = vec![v];
+            while v != start {
+                v = parent[v];
+                cycle.push(v);
+            }
+            cycle.reverse();
+            if best.as_ref().is_none_or(|b| cycle.len() < b.len()) {
+                best = Some(cycle);
+            }
+        }
+        for v in seen {
+            parent[v] = UNSEEN;
+        }
+    }
+    best
+}
+
+/// Checks the rules on their own and against the updates.
+pub fn validate_rules(rules: &HashMap<u32, Vec<u32>>, updates: &[Vec<u32>]) -> Validation {
+    let mut validation = Validation::default();
+
+    let mut counts: HashMap<(u32, u32), usize> = HashMap::new();
+    for (&before, afters) in rules {
+        for &after in afters {
+            *counts.entry((before, after)).or_default() += 1;
+        }
+    }
+    let mut distinct = counts.keys().copied().collect::<Vec<(u32, u32)>>();
+    distinct.sort();
+
+    for &rule in &distinct {
+        if counts[&rule] > 1 {
+            validation.duplicates.push(Duplicate {
+                rule,
+                count: counts[&rule],
+            });
+        }
+    }
+
+    let used = updates.iter().flatten().collect::<HashSet<&u32>>();
+    for &(before, after) in &distinct {
+        let mut missing = [before, after]
+            .into_iter()
+            .filter(|page| !used.contains(page))
+            .collect::<Vec<u32>>();
+        missing.dedup();
+        if !missing.is_empty() {
+            validation.unused.push(UnusedRule {
+                rule: (before, after),
+                missing,
+            });
+        }
+    }
+
+    let mut pages = distinct
+        .iter()
+        .flat_map(|&(before, after)| [before, after])
+        .collect::<Vec<u32>>();
+    pages.sort();
+    pages.dedup();
+    let node = pages
+        .iter()
+        .enumerate()
+        .map(|(i, &page)| (page, i))
+        .collect::<HashMap<u32, usize>>();
+    let mut adj = vec![Vec::new(); pages.len()];
+    for &(before, after) in &distinct {
+        adj[node[&before]].push(node[&after]);
+    }
+
+    let mut components = strongly_connected(&adj);
+    for component in &mut components {
+        component.sort();
+    }
+    components.sort();
+    let mut component_of = vec![0; pages.len()];
+    for (id, component) in components.iter().enumerate() {
+        for &v in component {
+            component_of[v] = id;
+        }
+    }
+    let mut parent = vec![UNSEEN; pages.len()];
+    for component in components.iter() {
+        // A lone page only contradicts itself with a rule like 5|5
+        if let Some(cycle) = shortest_cycle(&adj, component, &component_of, &mut parent) {
+            validation.contradictions.push(Contradiction {
+                pages: component.iter().map(|&v| pages[v]).collect(),
+                cycle: cycle.iter().map(|&v| pages[v]).collect(),
+            });
+        }
+    }
+    validation
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(pairs: &[(u32, u32)]) -> HashMap<u32, Vec<u32>> {
+        let mut rules: HashMap<u32, Vec<u32>> = HashMap::new();
+        for &(before, after) in pairs {
+            rules.entry(before).or_default().push(after);
+        }
+        rules
+    }
+
+    #[test]
+    fn test_consistent_rules() {
+        let rules = rules(&[(1, 2), (2, 3), (1, 3)]);
+        let validation = validate_rules(&rules, &[vec![1, 2, 3]]);
+        assert_eq!(validation, Validation::default());
+    }
+
+    #[test]
+    fn test_contradictions() {
+        // 1 -> 2 -> 3 -> 4 -> 1 has the shortcut 2 -> 4 -> 1 -> 2, and 5 <-> 6
+        // is a separate component; 7 only follows
+        let rules = rules(&[
+            (1, 2),
+            (2, 3),
+            (3, 4),
+            (4, 1),
+            (2, 4),
+            (5, 6),
+            (6, 5),
+            (6, 7),
+        ]);
+        let validation = validate_rules(&rules, &[vec![1, 2, 3, 4, 5, 6, 7]]);
+        assert_eq!(
+            validation.contradictions,
+            vec![
+                Contradiction {
+                    pages: vec![1, 2, 3, 4],
+                    cycle: vec![1, 2, 4],
+                },
+                Contradiction {
+                    pages: vec![5, 6],
+                    cycle: vec![5, 6],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_self_rule() {
+        let validation = validate_rules(&rules(&[(5, 5), (5, 6)]), &[vec![5, 6]]);
+        assert_eq!(
+            validation.contradictions,
+            vec![Contradiction {
+                pages: vec![5],
+                cycle: vec![5],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_and_unused() {
+        let rules = rules(&[(1, 2), (1, 2), (1, 2), (2, 9), (8, 9)]);
+        let validation = validate_rules(&rules, &[vec![1, 2]]);
+        assert_eq!(
+            validation.duplicates,
+            vec![Duplicate {
+                rule: (1, 2),
+                count: 3
+            }]
+        );
+        assert_eq!(
+            validation.unused,
+            vec![
+                UnusedRule {
+                    rule: (2, 9),
+                    missing: vec![9]
+                },
+                UnusedRule {
+                    rule: (8, 9),
+                    missing: vec![8, 9]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_long_chain() {
+        let pairs = (0..100_000)
+            .map(|i| (i, i + 1))
+            .collect::<Vec<(u32, u32)>>();
+        assert!(validate_rules(&rules(&pairs), &[])
+            .contradictions
+            .is_empty());
+        // Looking for the shortest cycle is quadratic in the component size
+        let mut cyclic = pairs[..1000].to_vec();
+        cyclic.push((1000, 0));
+        let validation = validate_rules(&rules(&cyclic), &[]);
+        assert_eq!(validation.contradictions[0].cycle.len(), 1001);
+    }
+}